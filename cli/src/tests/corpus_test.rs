@@ -338,6 +338,34 @@ fn test_feature_corpus_files() {
     }
 }
 
+#[test]
+fn test_template_included_ranges_skip_empty_ranges() {
+    let (parser_name, parser_code) = generate::generate_parser_for_grammar(
+        r#"{
+            "name": "test_template_included_ranges",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "PATTERN", "value": "[a-z]+"}}
+            }
+        }"#,
+    )
+    .unwrap();
+    let mut parser = Parser::new();
+    parser
+        .set_language(get_test_language(&parser_name, &parser_code, None))
+        .unwrap();
+
+    let input = b"<%%> a <% b %> c <%";
+    set_included_ranges(&mut parser, input, Some(("<%", "%>")));
+    let tree = parser.parse(input, None).unwrap();
+    let ranges = tree
+        .included_ranges()
+        .iter()
+        .map(|range| range.start_byte..range.end_byte)
+        .collect::<Vec<_>>();
+    assert_eq!(ranges, [9..12]);
+}
+
 fn check_consistent_sizes(tree: &Tree, input: &Vec<u8>) {
     fn check(node: Node, line_offsets: &Vec<usize>) {
         let start_byte = node.start_byte();
@@ -431,6 +459,12 @@ fn set_included_ranges(parser: &mut Parser, input: &[u8], delimiters: Option<(&s
                 .position(|win| win == end.as_bytes())
                 .map_or(input.len(), |ix| start_ix + ix);
             ix = end_ix;
+
+            // Adjacent delimiters, or a start delimiter at the end of the input, enclose
+            // no text, and the parser does not accept empty ranges.
+            if start_ix == end_ix {
+                continue;
+            }
             ranges.push(Range {
                 start_byte: start_ix,
                 end_byte: end_ix,
@@ -2,9 +2,12 @@ use super::helpers::edits::get_random_edit;
 use super::helpers::fixtures::{fixtures_dir, get_language, get_test_language};
 use super::helpers::random::Rand;
 use crate::generate::generate_parser_for_grammar;
-use crate::parse::perform_edit;
-use std::fs;
-use tree_sitter::{Node, Parser, Point, Tree};
+use crate::parse::{perform_edit, Edit};
+use std::{borrow::Cow, fs};
+use tree_sitter::{
+    byte_to_point, point_to_byte, Descend, InputEdit, Node, NodeKind, Parser, Point, Tree,
+    TreeVisitor,
+};
 
 const JSON_EXAMPLE: &'static str = r#"
 
@@ -199,6 +202,348 @@ fn test_node_children() {
     );
 }
 
+#[test]
+fn test_node_sibling_iterators() {
+    let tree = parse_json_example();
+    let array_node = tree.root_node().child(0).unwrap();
+    let false_node = array_node.named_child(1).unwrap();
+    assert_eq!(false_node.kind(), "false");
+
+    assert_eq!(
+        false_node
+            .following_siblings()
+            .map(|n| n.kind())
+            .collect::<Vec<_>>(),
+        &[",", "object", "]"]
+    );
+    assert_eq!(
+        false_node
+            .preceding_siblings()
+            .map(|n| n.kind())
+            .collect::<Vec<_>>(),
+        &[",", "number", "["]
+    );
+    assert_eq!(
+        false_node
+            .following_named_siblings()
+            .map(|n| n.kind())
+            .collect::<Vec<_>>(),
+        &["object"]
+    );
+    assert_eq!(
+        false_node
+            .preceding_named_siblings()
+            .map(|n| n.kind())
+            .collect::<Vec<_>>(),
+        &["number"]
+    );
+
+    let last_node = array_node.child(array_node.child_count() - 1).unwrap();
+    assert_eq!(last_node.following_siblings().next(), None);
+    assert_eq!(
+        array_node.child(0).unwrap().preceding_siblings().next(),
+        None
+    );
+}
+
+#[test]
+fn test_node_siblings_with_cursor() {
+    let tree = parse_json_example();
+    let array_node = tree.root_node().child(0).unwrap();
+    let false_node = array_node.named_child(1).unwrap();
+    let mut cursor = tree.walk();
+
+    assert_eq!(
+        false_node
+            .siblings(&mut cursor)
+            .map(|n| n.kind())
+            .collect::<Vec<_>>(),
+        &[",", "object", "]"]
+    );
+
+    for node in get_all_nodes(&tree) {
+        assert_eq!(
+            node.siblings(&mut cursor).collect::<Vec<_>>(),
+            node.following_siblings().collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(tree.root_node().siblings(&mut cursor).next(), None);
+}
+
+#[test]
+fn test_node_descendants_in_range() {
+    let tree = parse_json_example();
+    let root = tree.root_node();
+
+    let start = JSON_EXAMPLE.find("false").unwrap();
+    let end = JSON_EXAMPLE.find("{").unwrap() + 1;
+    assert_eq!(
+        root.descendants_in_range(start..end)
+            .map(|node| node.kind())
+            .collect::<Vec<_>>(),
+        &["array", "false", ",", "object", "{"]
+    );
+
+    // Nodes that merely touch the range are not included.
+    let start = JSON_EXAMPLE.find("123").unwrap() + 3;
+    assert_eq!(
+        root.descendants_in_range(start..start + 1)
+            .map(|node| node.kind())
+            .collect::<Vec<_>>(),
+        &["array", ","]
+    );
+
+    assert_eq!(
+        root.descendants_in_range(JSON_EXAMPLE.len()..JSON_EXAMPLE.len() + 10)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_node_walk_with_fields() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("python")).unwrap();
+    let tree = parser.parse("x = f(y)", None).unwrap();
+
+    assert_eq!(
+        tree.root_node()
+            .walk_with_fields()
+            .map(|(field_name, node, depth)| (field_name, node.kind(), depth))
+            .collect::<Vec<_>>(),
+        &[
+            (None, "expression_statement", 1),
+            (None, "assignment", 2),
+            (Some("left"), "identifier", 3),
+            (None, "=", 3),
+            (Some("right"), "call", 3),
+            (Some("function"), "identifier", 4),
+            (Some("arguments"), "argument_list", 4),
+            (None, "(", 5),
+            (None, "identifier", 5),
+            (None, ")", 5),
+        ]
+    );
+
+    let identifier_node = tree.root_node().descendant_for_byte_range(0, 1).unwrap();
+    assert_eq!(identifier_node.walk_with_fields().count(), 0);
+}
+
+#[test]
+fn test_node_find_first_and_find_all() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+
+    assert_eq!(
+        root_node
+            .find_all(|node| node.start_position().row == 3)
+            .iter()
+            .map(|node| node.kind())
+            .collect::<Vec<_>>(),
+        &["number", ","]
+    );
+    assert_eq!(
+        root_node
+            .find_all(|node| node.kind() == ",")
+            .iter()
+            .map(|node| node.start_position())
+            .collect::<Vec<_>>(),
+        &[Point::new(3, 5), Point::new(4, 7)]
+    );
+
+    // The node itself is included, and the walk stops at the first match.
+    assert_eq!(root_node.find_first(|_| true), Some(root_node));
+    let mut visited = 0;
+    let bracket_node = root_node.find_first(|node| {
+        visited += 1;
+        node.kind() == "["
+    });
+    assert_eq!(bracket_node.unwrap().start_byte(), 2);
+    assert_eq!(visited, 3);
+
+    let null_node = root_node.find_first(|node| node.kind() == "null").unwrap();
+    assert_eq!(null_node.start_position(), Point::new(6, 9));
+    assert_eq!(null_node.find_first(|node| node.kind() == "number"), None);
+}
+
+#[test]
+fn test_node_innermost_contained_in() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+    let source = JSON_EXAMPLE.as_bytes();
+    let text = |node: Option<Node>| node.map(|node| node.utf8_text(source).unwrap());
+
+    let number_node = root_node
+        .find_first(|node| node.kind() == "number")
+        .unwrap();
+    let range = number_node.byte_range();
+    assert_eq!(
+        root_node.innermost_contained_in(range.start, range.end),
+        Some(number_node)
+    );
+    assert_eq!(
+        text(root_node.innermost_contained_in(range.start - 2, range.end + 1)),
+        Some("123")
+    );
+
+    // When several nodes fit, the largest one wins.
+    let start = JSON_EXAMPLE.find("123").unwrap();
+    let end = JSON_EXAMPLE.find("false").unwrap() + 5;
+    assert_eq!(
+        text(root_node.innermost_contained_in(start, end)),
+        Some("false")
+    );
+    let object_node = root_node
+        .find_first(|node| node.kind() == "object")
+        .unwrap();
+    assert_eq!(
+        root_node.innermost_contained_in(start, object_node.end_byte()),
+        Some(object_node)
+    );
+
+    assert_eq!(
+        root_node.innermost_contained_in(0, JSON_EXAMPLE.len()),
+        Some(root_node)
+    );
+    assert_eq!(root_node.innermost_contained_in(start + 1, start + 2), None);
+}
+
+#[test]
+fn test_node_next_parse_state_lookaheads() {
+    let language = get_language("json");
+    let mut parser = Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("[1", None).unwrap();
+    let number_node = tree
+        .root_node()
+        .find_first(|node| node.kind() == "number")
+        .unwrap();
+    assert_ne!(number_node.parse_state(), u16::MAX);
+
+    let state = number_node.next_parse_state();
+    assert!((state as usize) < language.parse_state_count());
+    let mut lookahead = language.lookahead_iterator(state).unwrap();
+    let expected = lookahead.iter_names().collect::<Vec<_>>();
+    assert!(expected.contains(&","));
+    assert!(expected.contains(&"]"));
+    assert!(!expected.contains(&"number"));
+
+    assert!(language
+        .lookahead_iterator(language.parse_state_count() as u16)
+        .is_none());
+}
+
+#[test]
+fn test_node_path_from_root() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+    assert_eq!(root_node.path_from_root(), Vec::<usize>::new());
+    assert_eq!(tree.node_at_path(&[]), Some(root_node));
+
+    let null_node = root_node.find_first(|node| node.kind() == "null").unwrap();
+    let path = null_node.path_from_root();
+    assert_eq!(path, vec![0, 5, 1, 2]);
+    assert_eq!(tree.node_at_path(&path), Some(null_node));
+    assert_eq!(tree.node_at_path(&[0, 100]), None);
+
+    // The path still leads to the corresponding node after an unrelated edit.
+    let mut source = JSON_EXAMPLE.as_bytes().to_vec();
+    let mut old_tree = tree.clone();
+    let position = JSON_EXAMPLE.find("123").unwrap();
+    perform_edit(
+        &mut old_tree,
+        &mut source,
+        &Edit {
+            position,
+            deleted_length: 3,
+            inserted_text: b"4567".to_vec(),
+        },
+    );
+    let mut parser = Parser::new();
+    parser.set_language(get_language("json")).unwrap();
+    let new_tree = parser.parse(&source, Some(&old_tree)).unwrap();
+    let new_null_node = new_tree.node_at_path(&path).unwrap();
+    assert_eq!(new_null_node.kind(), "null");
+    assert_eq!(new_null_node.start_byte(), null_node.start_byte() + 1);
+}
+
+#[test]
+fn test_node_visit() {
+    struct Recorder {
+        events: Vec<String>,
+        skip_kind: &'static str,
+    }
+
+    impl<'tree> TreeVisitor<'tree> for Recorder {
+        fn enter(
+            &mut self,
+            node: Node<'tree>,
+            field: Option<&'static str>,
+            depth: usize,
+        ) -> Descend {
+            let field = field.map_or(String::new(), |field| format!("{field}: "));
+            self.events
+                .push(format!("{}{field}{}", "  ".repeat(depth), node.kind()));
+            if node.kind() == self.skip_kind {
+                Descend::Skip
+            } else {
+                Descend::Children
+            }
+        }
+
+        fn leave(&mut self, node: Node<'tree>) {
+            self.events.push(format!("/{}", node.kind()));
+        }
+    }
+
+    let tree = parse_json_example();
+    let object_node = tree
+        .root_node()
+        .find_first(|node| node.kind() == "object")
+        .unwrap();
+
+    let mut recorder = Recorder {
+        events: Vec::new(),
+        skip_kind: "string",
+    };
+    object_node.visit(&mut recorder);
+    assert_eq!(
+        recorder.events,
+        [
+            "object",
+            "  {",
+            "/{",
+            "  pair",
+            "    key: string",
+            "/string",
+            "    :",
+            "/:",
+            "    value: null",
+            "/null",
+            "/pair",
+            "  }",
+            "/}",
+            "/object",
+        ]
+    );
+
+    let mut recorder = Recorder {
+        events: Vec::new(),
+        skip_kind: "object",
+    };
+    tree.root_node().child(0).unwrap().visit(&mut recorder);
+    assert_eq!(
+        recorder
+            .events
+            .iter()
+            .filter(|e| e.ends_with("object"))
+            .count(),
+        2
+    );
+    assert!(!recorder.events.iter().any(|e| e.contains("pair")));
+    assert_eq!(recorder.events.last().unwrap(), "/array");
+}
+
 #[test]
 fn test_node_children_by_field_name() {
     let mut parser = Parser::new();
@@ -227,6 +572,29 @@ fn test_node_children_by_field_name() {
     );
 }
 
+#[test]
+fn test_node_tree_root() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+    for node in get_all_nodes(&tree) {
+        assert_eq!(node.tree_root(), root_node);
+    }
+
+    let tree2 = tree.clone();
+    let node = tree2.root_node().child(0).unwrap();
+    assert_eq!(node.tree_root(), tree2.root_node());
+    assert_ne!(node.tree_root(), root_node);
+}
+
+#[test]
+fn test_node_is_root() {
+    let tree = parse_json_example();
+    assert!(tree.root_node().is_root());
+    for node in get_all_nodes(&tree) {
+        assert_eq!(node.is_root(), node.parent().is_none());
+    }
+}
+
 #[test]
 fn test_node_parent_of_child_by_field_name() {
     let mut parser = Parser::new();
@@ -291,6 +659,31 @@ fn test_node_child_by_field_name_with_extra_hidden_children() {
     );
 }
 
+#[test]
+fn test_node_field_path() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("python")).unwrap();
+
+    let source = "x = f(y)";
+    let tree = parser.parse(source, None).unwrap();
+    let assignment_node = tree.root_node().child(0).unwrap().child(0).unwrap();
+    assert_eq!(assignment_node.kind(), "assignment");
+
+    let function_node = assignment_node.field_path(&["right", "function"]).unwrap();
+    assert_eq!(function_node.kind(), "identifier");
+    assert_eq!(function_node.utf8_text(source.as_bytes()).unwrap(), "f");
+    assert_eq!(
+        assignment_node.field_path(&["right"]),
+        assignment_node.child_by_field_name("right"),
+    );
+    assert_eq!(assignment_node.field_path(&[]), Some(assignment_node));
+
+    // Any missing step yields `None`.
+    assert_eq!(assignment_node.field_path(&["right", "body"]), None);
+    assert_eq!(assignment_node.field_path(&["left", "function"]), None);
+    assert_eq!(assignment_node.field_path(&["nonexistent"]), None);
+}
+
 #[test]
 fn test_node_named_child() {
     let tree = parse_json_example();
@@ -497,6 +890,367 @@ fn test_node_descendant_for_range() {
     assert_eq!(pair_node.end_position(), Point::new(6, 13));
 }
 
+#[test]
+fn test_node_named_descendant_for_point_range_in_subtree() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+    let object_node = root_node.child(0).unwrap().named_child(2).unwrap();
+    assert_eq!(object_node.kind(), "object");
+
+    // Searching from a subtree finds the same node as searching from the root.
+    let start = Point::new(6, 5);
+    let end = Point::new(6, 6);
+    let node = object_node
+        .named_descendant_for_point_range(start, end)
+        .unwrap();
+    assert_eq!(
+        root_node.named_descendant_for_point_range(start, end),
+        Some(node)
+    );
+    assert!(node.is_named());
+    assert!(node.start_position() <= start && node.end_position() >= end);
+
+    // Repeatedly expanding the selection walks up through the subtree.
+    let pair_node = object_node
+        .named_descendant_for_point_range(node.start_position(), Point::new(6, 13))
+        .unwrap();
+    assert_eq!(pair_node.kind(), "pair");
+    assert_eq!(
+        object_node.named_descendant_for_point_range(
+            object_node.start_position(),
+            object_node.end_position()
+        ),
+        Some(object_node)
+    );
+
+    // A selection outside of the subtree yields the subtree's root.
+    assert_eq!(
+        object_node.named_descendant_for_point_range(Point::new(3, 2), Point::new(3, 5)),
+        Some(object_node)
+    );
+}
+
+#[test]
+fn test_node_at_point() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+    let array_node = root_node.child(0).unwrap();
+
+    // Points within a leaf node.
+    let node = array_node.node_at_point(Point::new(3, 3)).unwrap();
+    assert_eq!(node.kind(), "number");
+    assert_eq!(array_node.named_node_at_point(Point::new(3, 3)), Some(node));
+
+    // At the boundary between two nodes, the node that starts there wins.
+    let node = array_node.node_at_point(Point::new(3, 2)).unwrap();
+    assert_eq!(node.kind(), "number");
+    let node = array_node.node_at_point(Point::new(3, 5)).unwrap();
+    assert_eq!(node.kind(), ",");
+    assert_eq!(
+        array_node.named_node_at_point(Point::new(3, 5)),
+        Some(array_node)
+    );
+
+    // Whitespace belongs to the smallest node that surrounds it.
+    assert_eq!(array_node.node_at_point(Point::new(3, 1)), Some(array_node));
+
+    // At the end of a node, the node itself is returned.
+    assert_eq!(
+        root_node.node_at_point(root_node.end_position()),
+        Some(root_node)
+    );
+
+    // Points outside of the node yield the node itself.
+    assert_eq!(array_node.node_at_point(Point::new(0, 0)), Some(array_node));
+    assert_eq!(
+        array_node.named_node_at_point(Point::new(20, 0)),
+        Some(array_node)
+    );
+}
+
+#[test]
+fn test_node_descendant_for_invalid_range() {
+    let tree = parse_json_example();
+    let array_node = tree.root_node().child(0).unwrap();
+    let colon_index = JSON_EXAMPLE.find(":").unwrap();
+
+    // The start of the range is after its end
+    assert_eq!(
+        array_node.descendant_for_byte_range(colon_index + 1, colon_index),
+        None
+    );
+    assert_eq!(
+        array_node.named_descendant_for_byte_range(colon_index + 1, colon_index),
+        None
+    );
+    assert_eq!(
+        array_node.descendant_for_point_range(Point::new(6, 8), Point::new(6, 7)),
+        None
+    );
+    assert_eq!(
+        array_node.named_descendant_for_point_range(Point::new(6, 8), Point::new(6, 7)),
+        None
+    );
+
+    // The range extends outside of the node, so the node itself is returned
+    assert_eq!(
+        array_node.descendant_for_byte_range(0, colon_index),
+        Some(array_node)
+    );
+    assert_eq!(
+        array_node.descendant_for_byte_range(colon_index, JSON_EXAMPLE.len() + 10),
+        Some(array_node)
+    );
+    assert_eq!(
+        array_node.named_descendant_for_point_range(Point::new(6, 7), Point::new(20, 0)),
+        Some(array_node)
+    );
+
+    // A range that exactly matches the node is still valid
+    assert_eq!(
+        array_node.descendant_for_byte_range(array_node.start_byte(), array_node.end_byte()),
+        Some(array_node)
+    );
+}
+
+#[test]
+fn test_node_text_with_provider() {
+    let tree = parse_json_example();
+
+    // Store the source code in small, non-contiguous chunks, like a rope.
+    let chunks = JSON_EXAMPLE
+        .as_bytes()
+        .chunks(4)
+        .enumerate()
+        .map(|(i, chunk)| (i * 4, chunk))
+        .collect::<Vec<_>>();
+    let rope_provider = |node: Node| {
+        let range = node.byte_range();
+        chunks.iter().filter_map(move |(offset, chunk)| {
+            let start = range.start.max(*offset);
+            let end = range.end.min(offset + chunk.len());
+            if start < end {
+                Some(&chunk[(start - offset)..(end - offset)])
+            } else {
+                None
+            }
+        })
+    };
+
+    for node in get_all_nodes(&tree) {
+        assert_eq!(
+            node.text_with_provider(&rope_provider),
+            node.utf8_text(JSON_EXAMPLE.as_bytes()).unwrap().as_bytes()
+        );
+        assert_eq!(
+            node.text_with_provider(JSON_EXAMPLE.as_bytes()),
+            node.utf8_text(JSON_EXAMPLE.as_bytes()).unwrap().as_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_node_utf8_text_checked() {
+    let tree = parse_json_example();
+    let array_node = tree.root_node().child(0).unwrap();
+    let number_node = array_node.named_child(0).unwrap();
+
+    assert_eq!(
+        number_node.utf8_text_checked(JSON_EXAMPLE.as_bytes()),
+        Some(Ok("123"))
+    );
+    assert!(array_node
+        .utf8_text_checked(&JSON_EXAMPLE.as_bytes()[..number_node.end_byte()])
+        .is_none());
+    assert!(number_node.utf8_text_checked(&[0; 20][..]).unwrap().is_ok());
+    assert!(number_node
+        .utf8_text_checked(&[0xff; 20][..])
+        .unwrap()
+        .is_err());
+}
+
+#[test]
+fn test_node_text_eq() {
+    let tree = parse_json_example();
+    let array_node = tree.root_node().child(0).unwrap();
+    let number_node = array_node.named_child(0).unwrap();
+    let false_node = array_node.named_child(1).unwrap();
+    let source = JSON_EXAMPLE.as_bytes();
+
+    assert!(number_node.text_eq(source, "123"));
+    assert!(!number_node.text_eq(source, "12"));
+    assert!(false_node.text_eq(source, "false"));
+    assert!(!false_node.text_eq(source, "FALSE"));
+    assert!(!false_node.text_eq(&source[..false_node.start_byte() + 2], "fa"));
+
+    let mut invalid_source = source.to_vec();
+    invalid_source[number_node.start_byte()] = 0xff;
+    assert!(!number_node.text_eq(&invalid_source, "123"));
+    assert!(false_node.text_eq(&invalid_source, "false"));
+}
+
+#[test]
+fn test_node_start_column_chars() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("json")).unwrap();
+
+    let source = "[\"é\", 1,\n\t\"ü\", 2]";
+    let tree = parser.parse(source, None).unwrap();
+    let array_node = tree.root_node().child(0).unwrap();
+    let first_number_node = array_node.named_child(1).unwrap();
+    let second_string_node = array_node.named_child(2).unwrap();
+    let second_number_node = array_node.named_child(3).unwrap();
+
+    assert_eq!(array_node.start_column_chars(source.as_bytes()), 0);
+    assert_eq!(first_number_node.start_position().column, 7);
+    assert_eq!(first_number_node.start_column_chars(source.as_bytes()), 6);
+    assert_eq!(second_string_node.start_position(), Point::new(1, 1));
+    assert_eq!(second_string_node.start_column_chars(source.as_bytes()), 1);
+    assert_eq!(second_number_node.start_position().column, 7);
+    assert_eq!(second_number_node.start_column_chars(source.as_bytes()), 6);
+}
+
+#[test]
+fn test_node_source_line() {
+    let tree = parse_json_example();
+    let source = JSON_EXAMPLE.as_bytes();
+    let array_node = tree.root_node().child(0).unwrap();
+    let number_node = array_node.named_child(0).unwrap();
+    let object_node = array_node.named_child(2).unwrap();
+    let pair_node = object_node.named_child(0).unwrap();
+
+    assert_eq!(number_node.source_line(source), b"  123,");
+    assert_eq!(pair_node.source_line(source), b"    \"x\": null");
+    assert_eq!(
+        object_node.source_line(source),
+        b"  {\n    \"x\": null\n  }"
+    );
+    assert_eq!(
+        array_node.source_line(source),
+        JSON_EXAMPLE.trim().as_bytes()
+    );
+
+    let mut parser = Parser::new();
+    parser.set_language(get_language("json")).unwrap();
+    let source = "[\r\n  1\r\n]";
+    let tree = parser.parse(source, None).unwrap();
+    let number_node = tree.root_node().child(0).unwrap().named_child(0).unwrap();
+    assert_eq!(number_node.source_line(source.as_bytes()), b"  1");
+}
+
+#[test]
+fn test_node_text_cow() {
+    let tree = parse_json_example();
+
+    let chunks = JSON_EXAMPLE
+        .as_bytes()
+        .chunks(4)
+        .enumerate()
+        .map(|(i, chunk)| (i * 4, chunk))
+        .collect::<Vec<_>>();
+    let rope_provider = |node: Node| {
+        let range = node.byte_range();
+        chunks.iter().filter_map(move |(offset, chunk)| {
+            let start = range.start.max(*offset);
+            let end = range.end.min(offset + chunk.len());
+            if start < end {
+                Some(&chunk[(start - offset)..(end - offset)])
+            } else {
+                None
+            }
+        })
+    };
+
+    for node in get_all_nodes(&tree) {
+        let text = node.utf8_text(JSON_EXAMPLE.as_bytes()).unwrap().as_bytes();
+
+        // Nodes that fit within a single chunk are borrowed.
+        let text_cow = node.text_cow(&rope_provider);
+        assert_eq!(text_cow, text);
+        let fits_in_chunk = node.start_byte() == node.end_byte()
+            || node.start_byte() / 4 == (node.end_byte() - 1) / 4;
+        assert_eq!(matches!(text_cow, Cow::Borrowed(_)), fits_in_chunk);
+
+        let text_cow = node.text_cow(JSON_EXAMPLE.as_bytes());
+        assert_eq!(text_cow, text);
+        assert!(matches!(text_cow, Cow::Borrowed(_)));
+    }
+}
+
+#[test]
+fn test_node_descendant_for_range_in_surrounding_whitespace() {
+    let tree = parse_json_example();
+    let root_node = tree.root_node();
+
+    // The example starts with blank lines, which are not part of the root node's
+    // extent, and ends with a newline.
+    assert!(root_node.start_byte() > 0);
+
+    // A cursor at the end of the file, after the trailing newline
+    let len = JSON_EXAMPLE.len();
+    let eof = byte_to_point(JSON_EXAMPLE.as_bytes(), len);
+    assert_eq!(
+        root_node.descendant_for_byte_range(len, len),
+        Some(root_node)
+    );
+    assert_eq!(
+        root_node.named_descendant_for_byte_range(len, len),
+        Some(root_node)
+    );
+    assert_eq!(root_node.node_at_point(eof), Some(root_node));
+    assert_eq!(root_node.named_node_at_point(eof), Some(root_node));
+
+    // A cursor at the start of the file, within the leading whitespace
+    assert_eq!(root_node.descendant_for_byte_range(0, 0), Some(root_node));
+    assert_eq!(
+        root_node.descendant_for_point_range(Point::new(0, 0), Point::new(0, 0)),
+        Some(root_node)
+    );
+    assert_eq!(root_node.node_at_point(Point::new(0, 0)), Some(root_node));
+}
+
+#[test]
+fn test_node_range_conversions() {
+    let tree = parse_json_example();
+    let number_node = tree.root_node().child(0).unwrap().named_child(0).unwrap();
+    let range = number_node.range();
+
+    let byte_range: std::ops::Range<usize> = range.into();
+    assert_eq!(byte_range, number_node.byte_range());
+    assert_eq!(&JSON_EXAMPLE[byte_range], "123");
+    assert_eq!(range.point_range(), Point::new(3, 2)..Point::new(3, 5));
+}
+
+#[test]
+fn test_byte_to_point_and_point_to_byte() {
+    let tree = parse_json_example();
+    let source = JSON_EXAMPLE.as_bytes();
+    for node in get_all_nodes(&tree) {
+        assert_eq!(
+            byte_to_point(source, node.start_byte()),
+            node.start_position()
+        );
+        assert_eq!(byte_to_point(source, node.end_byte()), node.end_position());
+        assert_eq!(
+            point_to_byte(source, node.start_position()),
+            Some(node.start_byte())
+        );
+    }
+
+    // Columns are measured in bytes, even with multibyte characters.
+    let source = "a\n\u{e9}b\nc".as_bytes();
+    assert_eq!(byte_to_point(source, 4), Point::new(1, 2));
+    assert_eq!(point_to_byte(source, Point::new(1, 2)), Some(4));
+
+    // The end of the source is a valid position, but positions past it are not.
+    assert_eq!(byte_to_point(source, source.len()), Point::new(2, 1));
+    assert_eq!(byte_to_point(source, source.len() + 5), Point::new(2, 1));
+    assert_eq!(point_to_byte(source, Point::new(2, 1)), Some(source.len()));
+    assert_eq!(point_to_byte(source, Point::new(2, 2)), None);
+    assert_eq!(point_to_byte(source, Point::new(3, 0)), None);
+    assert_eq!(point_to_byte(source, Point::new(0, 2)), None);
+}
+
 #[test]
 fn test_node_edit() {
     let mut code = JSON_EXAMPLE.as_bytes().to_vec();
@@ -529,6 +1283,36 @@ fn test_node_edit() {
     }
 }
 
+#[test]
+fn test_node_edit_on_detached_node() {
+    let tree = parse_json_example();
+    let number_node = tree.root_node().child(0).unwrap().named_child(0).unwrap();
+    let mut edited_node = number_node;
+
+    // Insert a blank line at the start of the document.
+    edited_node.edit(&InputEdit {
+        start_byte: 0,
+        old_end_byte: 0,
+        new_end_byte: 1,
+        start_position: Point::new(0, 0),
+        old_end_position: Point::new(0, 0),
+        new_end_position: Point::new(1, 0),
+    });
+
+    assert_eq!(edited_node.kind(), "number");
+    assert_eq!(edited_node.start_byte(), number_node.start_byte() + 1);
+    assert_eq!(edited_node.end_byte(), number_node.end_byte() + 1);
+    assert_eq!(edited_node.start_position(), Point::new(4, 2));
+    assert_eq!(edited_node.end_position(), Point::new(4, 5));
+
+    // The original node, and the tree it came from, are unaffected.
+    assert_eq!(number_node.start_position(), Point::new(3, 2));
+    assert_eq!(
+        tree.root_node().child(0).unwrap().named_child(0).unwrap(),
+        number_node
+    );
+}
+
 #[test]
 fn test_root_node_with_offset() {
     let mut parser = Parser::new();
@@ -572,6 +1356,137 @@ fn test_node_is_extra() {
     assert!(comment_node.is_extra());
 }
 
+#[test]
+fn test_node_is_kind() {
+    let tree = parse_json_example();
+    let language = tree.language();
+    let number_id = language.id_for_node_kind("number", true);
+    let object_id = language.id_for_node_kind("object", true);
+    let comma_id = language.id_for_node_kind(",", false);
+
+    let array_node = tree.root_node().child(0).unwrap();
+    let number_node = array_node.named_child(0).unwrap();
+    let comma_node = array_node.child(2).unwrap();
+    assert!(number_node.is_kind(number_id));
+    assert!(!number_node.is_kind(object_id));
+    assert!(comma_node.is_kind(comma_id));
+
+    assert!(number_node.is_any_kind(&[object_id, number_id]));
+    assert!(!comma_node.is_any_kind(&[object_id, number_id]));
+    assert!(!comma_node.is_any_kind(&[]));
+}
+
+#[test]
+fn test_node_child_of_kind() {
+    let tree = parse_json_example();
+    let language = tree.language();
+    let array_node = tree.root_node().child(0).unwrap();
+
+    assert_eq!(
+        array_node
+            .first_child_of_kind("number")
+            .unwrap()
+            .start_position(),
+        Point::new(3, 2)
+    );
+    assert_eq!(
+        array_node
+            .first_child_of_kind(",")
+            .unwrap()
+            .start_position(),
+        Point::new(3, 5)
+    );
+    assert_eq!(
+        array_node.last_child_of_kind(",").unwrap().start_position(),
+        Point::new(4, 7)
+    );
+    assert_eq!(array_node.first_child_of_kind("string"), None);
+
+    let object_id = language.id_for_node_kind("object", true);
+    assert_eq!(
+        array_node
+            .child_of_kind_id(object_id)
+            .unwrap()
+            .start_position(),
+        Point::new(5, 2)
+    );
+    assert_eq!(array_node.child_of_kind_id(u16::MAX), None);
+
+    let mut cursor = array_node.walk();
+    assert_eq!(
+        array_node
+            .children_of_kind(",", &mut cursor)
+            .map(|child| child.start_byte())
+            .collect::<Vec<_>>(),
+        vec![
+            JSON_EXAMPLE.find(',').unwrap(),
+            JSON_EXAMPLE.rfind(',').unwrap()
+        ]
+    );
+}
+
+#[test]
+fn test_node_ancestor_kinds() {
+    let tree = parse_json_example();
+    let language = tree.language();
+    let null_index = JSON_EXAMPLE.find("null").unwrap();
+    let null_node = tree
+        .root_node()
+        .descendant_for_byte_range(null_index, null_index + 4)
+        .unwrap();
+    assert_eq!(null_node.kind(), "null");
+
+    assert_eq!(
+        null_node
+            .ancestor_kinds()
+            .into_iter()
+            .map(|id| language.node_kind_for_id(id).unwrap())
+            .collect::<Vec<_>>(),
+        &["pair", "object", "array", "document"]
+    );
+    assert_eq!(tree.root_node().ancestor_kinds(), Vec::<u16>::new());
+
+    let object_id = language.id_for_node_kind("object", true);
+    let number_id = language.id_for_node_kind("number", true);
+    let null_id = language.id_for_node_kind("null", true);
+    assert!(null_node.has_ancestor_of_kind(object_id));
+    assert!(!null_node.has_ancestor_of_kind(number_id));
+    assert!(!null_node.has_ancestor_of_kind(null_id));
+    assert!(!tree.root_node().has_ancestor_of_kind(object_id));
+}
+
+#[test]
+fn test_node_kind_class() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let tree = parser.parse("foo(/* hi */);", None).unwrap();
+    let call_node = tree.root_node().child(0).unwrap().child(0).unwrap();
+    let arguments_node = call_node.child_by_field_name("arguments").unwrap();
+    assert_eq!(call_node.kind_class(), NodeKind::Named);
+    assert_eq!(
+        arguments_node.child(0).unwrap().kind_class(),
+        NodeKind::Anonymous
+    );
+    assert_eq!(
+        arguments_node.child(1).unwrap().kind_class(),
+        NodeKind::Extra
+    );
+
+    let tree = parser.parse("foo(;", None).unwrap();
+    let error_node = get_all_nodes(&tree)
+        .into_iter()
+        .find(|node| node.is_error())
+        .unwrap();
+    assert_eq!(error_node.kind_class(), NodeKind::Error);
+
+    parser.set_language(get_language("c")).unwrap();
+    let tree = parser.parse("int x", None).unwrap();
+    let declaration_node = tree.root_node().child(0).unwrap();
+    let semicolon_node = declaration_node.child(2).unwrap();
+    assert!(semicolon_node.is_missing());
+    assert_eq!(semicolon_node.kind_class(), NodeKind::Missing);
+}
+
 #[test]
 fn test_node_sexp() {
     let mut parser = Parser::new();
@@ -589,6 +1504,23 @@ fn test_node_sexp() {
     assert_eq!(identifier_node.to_sexp(), "(identifier)");
 }
 
+#[test]
+fn test_node_sexp_with_ranges() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let tree = parser.parse("a(b);", None).unwrap();
+    let root_node = tree.root_node();
+    assert_eq!(
+        root_node.to_sexp_with_ranges(),
+        "(program [0, 5] (expression_statement [0, 5] \
+         (call_expression [0, 4] function: (identifier [0, 1]) \
+         arguments: (arguments [1, 4] (identifier [2, 3])))))"
+    );
+
+    let paren_node = root_node.descendant_for_byte_range(1, 1).unwrap();
+    assert_eq!(paren_node.to_sexp_with_ranges(), "(\"(\" [1, 2])");
+}
+
 #[test]
 fn test_node_field_names() {
     let (parser_name, parser_code) = generate_parser_for_grammar(
@@ -699,6 +1631,24 @@ fn test_node_field_names() {
     assert_eq!(cursor.field_name(), Some("field_3"));
 }
 
+#[test]
+fn test_language_bulk_id_lookups() {
+    let language = get_language("javascript");
+    assert_eq!(
+        language.field_ids_for_names(&["body", "not_a_field", "name"]),
+        vec![
+            language.field_id_for_name("body"),
+            None,
+            language.field_id_for_name("name"),
+        ]
+    );
+    assert_eq!(
+        language.ids_for_node_kinds(&["identifier", "not_a_kind"], true),
+        vec![language.id_for_node_kind("identifier", true), 0]
+    );
+    assert!(language.field_ids_for_names(&[]).is_empty());
+}
+
 #[test]
 fn test_node_field_calls_in_language_without_fields() {
     let (parser_name, parser_code) = generate_parser_for_grammar(
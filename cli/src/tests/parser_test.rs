@@ -12,7 +12,7 @@ use std::{
     sync::atomic::{AtomicUsize, Ordering},
     thread, time,
 };
-use tree_sitter::{IncludedRangesError, InputEdit, LogType, Parser, Point, Range};
+use tree_sitter::{IncludedRangesError, InputEdit, LogType, Parser, ParserPool, Point, Range};
 
 #[test]
 fn test_parsing_simple_string() {
@@ -81,6 +81,74 @@ fn test_parsing_with_logging() {
     assert!(row_starts_from_0);
 }
 
+#[test]
+fn test_parser_reset_full() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    parser.set_logger(Some(Box::new(|_, _| {})));
+
+    let source_code = "a; b; c;";
+    let start_byte = source_code.find("b").unwrap();
+    parser
+        .set_included_ranges(&[Range {
+            start_byte,
+            end_byte: start_byte + 2,
+            start_point: Point::new(0, start_byte),
+            end_point: Point::new(0, start_byte + 2),
+        }])
+        .unwrap();
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.root_node().named_child_count(), 1);
+
+    parser.reset_full();
+    assert!(parser.logger().is_none());
+    let tree = parser.parse(source_code, None).unwrap();
+    assert_eq!(tree.root_node().start_byte(), 0);
+    assert_eq!(tree.root_node().named_child_count(), 3);
+}
+
+#[test]
+fn test_parser_pool() {
+    let pool = ParserPool::new();
+    let javascript = get_language("javascript");
+    let rust = get_language("rust");
+
+    {
+        let mut parser = pool.get(javascript).unwrap();
+        parser.set_timeout_micros(1_000_000);
+        parser.set_logger(Some(Box::new(|_, _| {})));
+        let tree = parser.parse("a;", None).unwrap();
+        assert_eq!(tree.root_node().kind(), "program");
+
+        // Another parser is created while the first one is in use.
+        let other_parser = pool.get(javascript).unwrap();
+        assert_eq!(other_parser.language(), Some(javascript));
+    }
+
+    // Parsers are returned to the pool in a clean state.
+    let mut parser = pool.get(javascript).unwrap();
+    assert_eq!(parser.timeout_micros(), 0);
+    assert!(parser.logger().is_none());
+    let tree = parser.parse("b;", None).unwrap();
+    assert_eq!(tree.root_node().kind(), "program");
+
+    let mut parser = pool.get(rust).unwrap();
+    let tree = parser.parse("struct A {}", None).unwrap();
+    assert_eq!(tree.root_node().kind(), "source_file");
+
+    // The pool can be shared between threads.
+    let pool = &pool;
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(move || {
+                let mut parser = pool.get(rust).unwrap();
+                let tree = parser.parse("fn main() {}", None).unwrap();
+                assert_eq!(tree.root_node().kind(), "source_file");
+            });
+        }
+    });
+}
+
 #[test]
 #[cfg(unix)]
 fn test_parsing_with_debug_graph_enabled() {
@@ -151,6 +219,71 @@ fn test_parsing_with_custom_utf8_input() {
     assert_eq!(root.child(0).unwrap().kind(), "function_item");
 }
 
+#[test]
+fn test_parsing_with_callbacks_returning_different_types() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("rust")).unwrap();
+
+    let lines = ["pub fn foo() {\n", "  1\n", "}\n"];
+    let source_code = lines.concat();
+    let expected_sexp = parser
+        .parse(&source_code, None)
+        .unwrap()
+        .root_node()
+        .to_sexp();
+
+    // Borrowed strings
+    let tree = parser
+        .parse_with(&mut |i, _| source_code.get(i..).unwrap_or(""), None)
+        .unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected_sexp);
+
+    // Owned strings, one line at a time
+    let tree = parser
+        .parse_with(
+            &mut |_, position| {
+                lines
+                    .get(position.row)
+                    .map_or(String::new(), |line| line[position.column..].to_string())
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected_sexp);
+
+    // Owned byte vectors
+    let tree = parser
+        .parse_with(
+            &mut |i, _| {
+                source_code.as_bytes()[i..]
+                    .iter()
+                    .take(3)
+                    .copied()
+                    .collect::<Vec<_>>()
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected_sexp);
+
+    // Byte string literals and slices of different lengths
+    let tree = parser
+        .parse_with_slices(
+            &mut |i, _| {
+                if i == 0 {
+                    b"pub fn foo() {\n"
+                } else if i < source_code.len() {
+                    &source_code.as_bytes()[i..]
+                } else {
+                    b""
+                }
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected_sexp);
+}
+
 #[test]
 fn test_parsing_with_custom_utf16_input() {
     let mut parser = Parser::new();
@@ -270,6 +403,38 @@ fn test_parsing_text_with_byte_order_mark() {
     assert_eq!(tree.root_node().start_byte(), 3);
 }
 
+#[test]
+fn test_parsing_utf16_with_explicit_byte_order() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("rust")).unwrap();
+
+    let source_code = "\u{FEFF}fn \u{e9}() { \"\u{1F600}\" }";
+    let code_units = source_code.encode_utf16().collect::<Vec<_>>();
+    let le_bytes = code_units
+        .iter()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect::<Vec<_>>();
+    let be_bytes = code_units
+        .iter()
+        .flat_map(|unit| unit.to_be_bytes())
+        .collect::<Vec<_>>();
+
+    let expected_tree = parser.parse_utf16(&code_units, None).unwrap();
+    let le_tree = parser.parse_utf16_le(&le_bytes, None).unwrap();
+    let be_tree = parser.parse_utf16_be(&be_bytes, None).unwrap();
+    assert_eq!(
+        le_tree.root_node().to_sexp(),
+        expected_tree.root_node().to_sexp()
+    );
+    assert_eq!(
+        be_tree.root_node().to_sexp(),
+        expected_tree.root_node().to_sexp()
+    );
+    assert!(!le_tree.root_node().has_error());
+    assert_eq!(le_tree.root_node().start_byte(), 2);
+    assert_eq!(be_tree.root_node().byte_range(), 2..le_bytes.len());
+}
+
 #[test]
 fn test_parsing_invalid_chars_at_eof() {
     let mut parser = Parser::new();
@@ -360,6 +525,63 @@ fn test_parsing_after_editing_beginning_of_code() {
     assert_eq!(recorder.strings_read(), vec!["123 || 5 "]);
 }
 
+#[test]
+fn test_parsing_with_reparse() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+
+    let mut tree = parser.parse("a + b;", None).unwrap();
+    let edit = InputEdit {
+        start_byte: 4,
+        old_end_byte: 5,
+        new_end_byte: 9,
+        start_position: Point::new(0, 4),
+        old_end_position: Point::new(0, 5),
+        new_end_position: Point::new(0, 9),
+    };
+    let new_tree = parser.reparse(&mut tree, &edit, "a + c * d;").unwrap();
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        concat!(
+            "(program (expression_statement (binary_expression ",
+            "left: (identifier) ",
+            "right: (binary_expression left: (identifier) right: (identifier)))))",
+        )
+    );
+
+    // The old tree has been edited to match the new text.
+    assert!(tree.root_node().has_changes());
+    assert_eq!(tree.root_node().end_byte(), 10);
+    assert!(tree
+        .changed_ranges(&new_tree)
+        .any(|range| range.start_byte <= 4 && range.end_byte >= 9));
+}
+
+#[test]
+fn test_parsing_with_errors() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("c")).unwrap();
+
+    let (tree, errors) = parser
+        .parse_with_errors("int a = 1;\nint b = 2;\n", None)
+        .unwrap();
+    assert!(!tree.root_node().has_error());
+    assert!(errors.is_empty());
+
+    let (tree, errors) = parser
+        .parse_with_errors("int a = 1;\nint b = 2\n", None)
+        .unwrap();
+    assert!(tree.root_node().has_error());
+    assert_eq!(
+        errors,
+        tree.errors()
+            .iter()
+            .map(|node| node.range())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(errors, &[Range::empty_at(20, Point::new(1, 9))],);
+}
+
 #[test]
 fn test_parsing_after_editing_end_of_code() {
     let mut parser = Parser::new();
@@ -694,6 +916,56 @@ fn test_parsing_with_a_timeout() {
     assert_eq!(tree.root_node().child(0).unwrap().kind(), "array");
 }
 
+#[test]
+fn test_parsing_with_a_timeout_reports_progress() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("json")).unwrap();
+    assert_eq!(parser.current_byte_offset(), 0);
+
+    let mut input = |offset: usize, _| -> &[u8] {
+        if offset == 0 {
+            b" ["
+        } else {
+            b",0"
+        }
+    };
+
+    // A halted parse returns no tree, but records how far it got.
+    parser.set_timeout_micros(1000);
+    assert!(parser.parse_with(&mut input, None).is_none());
+    let first_offset = parser.current_byte_offset();
+    assert!(first_offset > 0);
+
+    // Resuming the parse advances past the previous offset.
+    assert!(parser.parse_with(&mut input, None).is_none());
+    assert!(parser.current_byte_offset() > first_offset);
+
+    // Resetting the parser discards the progress.
+    parser.reset();
+    assert_eq!(parser.current_byte_offset(), 0);
+
+    // A completed parse leaves no parse in progress.
+    parser.set_timeout_micros(0);
+    parser.parse("[1, 2]", None).unwrap();
+    assert_eq!(parser.current_byte_offset(), 0);
+}
+
+#[test]
+fn test_parsing_with_a_timeout_duration() {
+    let mut parser = Parser::new();
+    assert_eq!(parser.timeout(), time::Duration::ZERO);
+
+    parser.set_timeout(time::Duration::from_millis(5));
+    assert_eq!(parser.timeout_micros(), 5000);
+    assert_eq!(parser.timeout(), time::Duration::from_millis(5));
+
+    parser.set_timeout_micros(250);
+    assert_eq!(parser.timeout(), time::Duration::from_micros(250));
+
+    parser.set_timeout(time::Duration::ZERO);
+    assert_eq!(parser.timeout_micros(), 0);
+}
+
 #[test]
 fn test_parsing_with_a_timeout_and_a_reset() {
     let mut parser = Parser::new();
@@ -799,6 +1071,36 @@ fn test_parsing_with_timeout_and_no_completion() {
     });
 }
 
+#[test]
+fn test_parsing_with_a_progress_callback() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("json")).unwrap();
+    let source = format!("[{}0]", "0,".repeat(1000));
+
+    // Halt once the parser has made it a quarter of the way through the document.
+    let mut offsets = Vec::new();
+    let tree = parser.parse_with_progress(&source, None, |state| {
+        offsets.push(state.current_byte_offset());
+        state.current_byte_offset() < source.len() / 4
+    });
+    assert!(tree.is_none());
+    assert!(offsets.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(*offsets.last().unwrap() >= source.len() / 4);
+    assert!(*offsets.last().unwrap() < source.len());
+
+    // Start over, and let the parse run to completion.
+    parser.reset();
+    let mut call_count = 0;
+    let tree = parser
+        .parse_with_progress(&source, None, |_| {
+            call_count += 1;
+            true
+        })
+        .unwrap();
+    assert!(call_count > 0);
+    assert_eq!(tree.root_node().child(0).unwrap().kind(), "array");
+}
+
 // Included Ranges
 
 #[test]
@@ -832,6 +1134,46 @@ fn test_parsing_with_one_included_range() {
     assert_eq!(js_tree.included_ranges(), &[script_content_node.range()]);
 }
 
+#[test]
+fn test_parsing_injection() {
+    let source_code = "<span>hi</span>\n<script>\nconsole.log('sup');\n</script>";
+
+    let mut parser = Parser::new();
+    parser.set_language(get_language("html")).unwrap();
+    let html_tree = parser.parse(source_code, None).unwrap();
+    let script_content_node = html_tree.root_node().child(1).unwrap().child(1).unwrap();
+    assert_eq!(script_content_node.kind(), "raw_text");
+
+    parser.set_language(get_language("javascript")).unwrap();
+    let js_tree = parser
+        .parse_injection(source_code.as_bytes(), &[script_content_node.range()], None)
+        .unwrap()
+        .unwrap();
+
+    // Positions are relative to the whole document.
+    let statement_node = js_tree.root_node().child(0).unwrap();
+    assert_eq!(statement_node.kind(), "expression_statement");
+    assert_eq!(
+        statement_node.start_byte(),
+        source_code.find("console").unwrap()
+    );
+    assert_eq!(statement_node.start_position(), Point::new(2, 0));
+    assert_eq!(js_tree.included_ranges(), &[script_content_node.range()]);
+
+    // The parser's included ranges are restored afterward.
+    let js_tree = parser.parse("a; b;", None).unwrap();
+    assert_eq!(js_tree.root_node().named_child_count(), 2);
+
+    // Invalid ranges are reported as errors.
+    let range = script_content_node.range();
+    assert_eq!(
+        parser
+            .parse_injection(source_code.as_bytes(), &[range, range], None)
+            .err(),
+        Some(IncludedRangesError(1))
+    );
+}
+
 #[test]
 fn test_parsing_with_multiple_included_ranges() {
     let source_code = "html `<div>Hello, ${name.toUpperCase()}, it's <b>${now()}</b>.</div>`";
@@ -1002,6 +1344,40 @@ fn test_parsing_error_in_invalid_included_ranges() {
         }])
         .unwrap_err();
     assert_eq!(error, IncludedRangesError(0));
+
+    // Range is empty
+    let error = parser
+        .set_included_ranges(&[
+            Range {
+                start_byte: 0,
+                end_byte: 5,
+                start_point: Point::new(0, 0),
+                end_point: Point::new(0, 5),
+            },
+            Range {
+                start_byte: 8,
+                end_byte: 8,
+                start_point: Point::new(0, 8),
+                end_point: Point::new(0, 8),
+            },
+        ])
+        .unwrap_err();
+    assert_eq!(error, IncludedRangesError(1));
+
+    // Rejected ranges leave the previous ranges in place
+    let range = Range {
+        start_byte: 2,
+        end_byte: 4,
+        start_point: Point::new(0, 2),
+        end_point: Point::new(0, 4),
+    };
+    parser.set_language(get_language("javascript")).unwrap();
+    parser.set_included_ranges(&[range]).unwrap();
+    parser
+        .set_included_ranges(&[Range::empty_at(3, Point::new(0, 3))])
+        .unwrap_err();
+    let tree = parser.parse("a;bc;d", None).unwrap();
+    assert_eq!(tree.included_ranges(), &[range]);
 }
 
 #[test]
@@ -6,9 +6,17 @@ use super::helpers::{
 };
 use lazy_static::lazy_static;
 use rand::{prelude::StdRng, SeedableRng};
-use std::{env, fmt::Write};
+use std::{
+    borrow::Cow,
+    env,
+    fmt::Write,
+    iter,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
 use tree_sitter::{
-    CaptureQuantifier, Language, Node, Parser, Point, Query, QueryCapture, QueryCursor, QueryError,
+    highlight, CaptureQuantifier, InputEdit, Language, Node, Parser, Point, PredicateInfo,
+    PredicateInfoArg, PredicateRegistry, Query, QueryCapture, QueryCursor, QueryError,
     QueryErrorKind, QueryMatch, QueryPredicate, QueryPredicateArg, QueryProperty,
 };
 use unindent::Unindent;
@@ -266,6 +274,196 @@ fn test_query_errors_on_invalid_symbols() {
     });
 }
 
+#[test]
+fn test_query_error_location() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        let source = "(if_statement)\n  (if_statement (arrayyyyy))";
+        let error = Query::new(language, source).unwrap_err();
+        assert_eq!(error.location(source), Some(Point::new(1, 17)));
+        assert_eq!(
+            error.location(source),
+            Some(Point::new(error.row, error.column))
+        );
+        assert_eq!(error.location(""), None);
+
+        let source = "((identifier) @id (#eq? @id))";
+        let error = Query::new(language, source).unwrap_err();
+        assert_eq!(error.kind, QueryErrorKind::Predicate);
+        assert_eq!(error.location(source), None);
+    });
+}
+
+#[test]
+fn test_query_with_custom_predicates() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let mut registry = PredicateRegistry::new();
+        registry.register(
+            "contains?",
+            |predicate| match predicate.args.as_slice() {
+                [QueryPredicateArg::Capture(_), QueryPredicateArg::String(_)] => Ok(()),
+                _ => Err("#contains? expects a capture and a string".to_string()),
+            },
+            |predicate, m, text| match predicate.args.as_slice() {
+                [QueryPredicateArg::Capture(index), QueryPredicateArg::String(substring)] => {
+                    m.nodes_for_capture_index(*index).all(|node| {
+                        String::from_utf8(text(node))
+                            .unwrap()
+                            .contains(&**substring)
+                    })
+                }
+                _ => unreachable!(),
+            },
+        );
+        assert!(registry.contains("contains?"));
+        assert!(registry.contains("eq?"));
+        assert!(!registry.contains("other?"));
+
+        let query = Query::new_with_predicates(
+            language,
+            r#"((identifier) @id (#contains? @id "foo") (#other? @id))"#,
+            &registry,
+        )
+        .unwrap();
+        assert_eq!(
+            query.general_predicates(0),
+            &[QueryPredicate {
+                operator: "other?".to_string().into_boxed_str(),
+                args: vec![QueryPredicateArg::Capture(0)],
+            }]
+        );
+
+        let source = "foo(); foobar(); bar();";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+        for query in [&query, &query.clone()] {
+            let matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+            assert_eq!(
+                collect_matches(matches, query, source),
+                &[(0, vec![("id", "foo")]), (0, vec![("id", "foobar")])]
+            );
+        }
+
+        assert_eq!(
+            Query::new_with_predicates(language, "((identifier) @id (#contains? @id))", &registry)
+                .unwrap_err(),
+            QueryError {
+                kind: QueryErrorKind::Predicate,
+                row: 0,
+                column: 0,
+                offset: 0,
+                message: "#contains? expects a capture and a string".to_string(),
+            }
+        );
+    });
+}
+
+#[test]
+fn test_query_error_location_for_invalid_regex() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        let source = r##"
+            (identifier) @a
+            ((identifier) @b
+              (#eq? @b "#match?") ; (#match? @b "x")
+              (#match? @b "ok")
+              (#not-match? @b "a("))
+        "##
+        .unindent();
+        let error = Query::new(language, &source).unwrap_err();
+        assert_eq!(
+            error,
+            QueryError {
+                kind: QueryErrorKind::Predicate,
+                row: 4,
+                column: 18,
+                offset: source.find(r#""a(""#).unwrap(),
+                message: "Invalid regex 'a('".to_string(),
+            }
+        );
+        assert_eq!(error.location(&source), Some(Point::new(4, 18)));
+    });
+}
+
+#[test]
+fn test_query_patterns() {
+    let source = r#"
+        ; A comment with (parens
+        (identifier) @variable
+
+        [
+          (string)
+          "(" ; another comment )
+        ] @punctuation
+        "if" @keyword
+        _
+        (comment)+ ((number) (number))* @numbers
+        body: (block)
+        ((identifier) @constant
+          (#match? @constant "^[A-Z]+\)$"))
+        (unterminated (pattern
+    "#;
+    assert_eq!(
+        Query::patterns(source),
+        &[
+            "(identifier) @variable",
+            "[\n          (string)\n          \"(\" ; another comment )\n        ] @punctuation",
+            "\"if\" @keyword",
+            "_",
+            "(comment)+",
+            "((number) (number))* @numbers",
+            "body: (block)",
+            "((identifier) @constant\n          (#match? @constant \"^[A-Z]+\\)$\"))",
+            "(unterminated (pattern",
+        ]
+    );
+    assert_eq!(Query::patterns("  ; nothing here\n"), Vec::<&str>::new());
+}
+
+#[test]
+fn test_query_patterns_can_be_compiled_separately() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let source = "(identifier) @a (not_a_node) @b (string) @c";
+        let patterns = Query::patterns(source);
+        assert_eq!(patterns.len(), 3);
+        let broken = patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| Query::new(language, pattern).is_err())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(broken, &[1]);
+    });
+}
+
+#[test]
+fn test_query_error_into_crate_error() {
+    fn parse_and_query(source: &str) -> Result<usize, tree_sitter::Error> {
+        let language = get_language("javascript");
+        let mut parser = Parser::new();
+        parser.set_language(language)?;
+        let query = Query::new(language, source)?;
+        Ok(query.pattern_count())
+    }
+
+    assert_eq!(parse_and_query("(identifier) @a").unwrap(), 1);
+
+    let error = parse_and_query("(identifier) @a (").unwrap_err();
+    let query_error = Query::new(get_language("javascript"), "(identifier) @a (").unwrap_err();
+    assert_eq!(error.to_string(), query_error.to_string());
+    assert!(std::error::Error::source(&error).is_some());
+    match error {
+        tree_sitter::Error::Query(error) => assert_eq!(error, query_error),
+        _ => panic!("unexpected error {error:?}"),
+    }
+}
+
 #[test]
 fn test_query_errors_on_invalid_predicates() {
     allocations::record(|| {
@@ -477,6 +675,26 @@ fn test_query_verifies_possible_patterns_with_aliased_parent_nodes() {
     });
 }
 
+#[test]
+fn test_query_from_bytes() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        let query = Query::from_bytes(language, b"(identifier) @id (#eq? @id \"\xff\")").unwrap();
+        assert_eq!(query.capture_names(), &["id".to_string()]);
+        assert_eq!(query.pattern_count(), 1);
+
+        assert_eq!(
+            Query::from_bytes(language, b"(if_statement)\n(clas)").unwrap_err(),
+            Query::new(language, "(if_statement)\n(clas)").unwrap_err(),
+        );
+        assert_eq!(
+            Query::from_bytes(language, b"(if_statement").unwrap_err(),
+            Query::new(language, "(if_statement").unwrap_err(),
+        );
+    });
+}
+
 #[test]
 fn test_query_matches_with_simple_pattern() {
     allocations::record(|| {
@@ -499,6 +717,41 @@ fn test_query_matches_with_simple_pattern() {
     });
 }
 
+#[test]
+fn test_query_all_matches_owned() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            "(function_declaration name: (identifier) @fn-name)",
+        )
+        .unwrap();
+
+        let source = "function one() { two(); function three() {} }";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.all_matches_owned(&query, tree.root_node(), source.as_bytes());
+
+        // The cursor can be reused while the previous matches are still alive.
+        let other_matches = cursor.all_matches_owned(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(other_matches.len(), 2);
+
+        matches.sort_by_key(|m| std::cmp::Reverse(m.captures[0].node.start_byte()));
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (
+                    m.pattern_index,
+                    m.captures[0].node.utf8_text(source.as_bytes()).unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            &[(0, "three"), (0, "one")],
+        );
+    });
+}
+
 #[test]
 fn test_query_matches_with_multiple_on_same_root() {
     allocations::record(|| {
@@ -1979,150 +2232,434 @@ fn test_query_matches_within_point_range() {
 }
 
 #[test]
-fn test_query_captures_within_byte_range() {
+fn test_query_cursor_builder() {
     allocations::record(|| {
-        let language = get_language("c");
-        let query = Query::new(
-            language,
-            "
-            (call_expression
-                function: (identifier) @function
-                arguments: (argument_list (string_literal) @string.arg))
-
-            (string_literal) @string
-           ",
-        )
-        .unwrap();
+        let language = get_language("javascript");
+        let query = Query::new(language, "(identifier) @element").unwrap();
 
-        let source = r#"DEFUN ("safe-length", Fsafe_length, Ssafe_length, 1, 1, 0)"#;
+        let source = "
+            [
+              a, b,
+              c, d,
+              e, f,
+            ]
+        "
+        .unindent();
 
         let mut parser = Parser::new();
         parser.set_language(language).unwrap();
         let tree = parser.parse(&source, None).unwrap();
 
-        let mut cursor = QueryCursor::new();
-        let captures =
-            cursor
-                .set_byte_range(3..27)
-                .captures(&query, tree.root_node(), source.as_bytes());
+        let mut cursor = QueryCursor::builder().match_limit(32).build();
+        assert_eq!(cursor.match_limit(), 32);
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(collect_matches(matches, &query, &source).len(), 6);
 
+        let mut cursor = QueryCursor::builder()
+            .point_range(Point::new(2, 0)..Point::new(3, 3))
+            .build();
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
         assert_eq!(
-            collect_captures(captures, &query, source),
+            collect_matches(matches, &query, &source),
             &[
-                ("function", "DEFUN"),
-                ("string.arg", "\"safe-length\""),
-                ("string", "\"safe-length\""),
+                (0, vec![("element", "c")]),
+                (0, vec![("element", "d")]),
+                (0, vec![("element", "e")]),
             ]
         );
+
+        // When both ranges are given, only nodes intersecting both are visited.
+        let start_byte = source.find('d').unwrap();
+        let mut cursor = QueryCursor::builder()
+            .byte_range(start_byte..source.len())
+            .point_range(Point::new(1, 0)..Point::new(3, 3))
+            .build();
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, &source),
+            &[(0, vec![("element", "d")]), (0, vec![("element", "e")])]
+        );
     });
 }
 
 #[test]
-fn test_query_matches_with_unrooted_patterns_intersecting_byte_range() {
+fn test_query_cursor_settings_persist_across_executions() {
     allocations::record(|| {
-        let language = get_language("rust");
-        let query = Query::new(
-            language,
-            r#"
-            ("{" @left "}" @right)
-            ("<" @left ">" @right)
-            "#,
-        )
-        .unwrap();
+        let language = get_language("javascript");
+        let query = Query::new(language, "(identifier) @element").unwrap();
 
-        let source = "mod a { fn a<B: C, D: E>(f: B) { g(f) } }";
+        let source = "[a, b, c, [d, e], f]";
 
         let mut parser = Parser::new();
         parser.set_language(language).unwrap();
         let tree = parser.parse(&source, None).unwrap();
+        let inner_array = tree
+            .root_node()
+            .named_descendant_for_byte_range(10, 10)
+            .unwrap();
+        assert_eq!(inner_array.kind(), "array");
+
         let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(4..13).set_match_limit(32);
 
-        // within the type parameter list
-        let offset = source.find("D: E>").unwrap();
-        let matches = cursor.set_byte_range(offset..offset).matches(
-            &query,
-            tree.root_node(),
-            source.as_bytes(),
-        );
+        for _ in 0..2 {
+            let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+            assert_eq!(
+                collect_matches(matches, &query, source),
+                &[
+                    (0, vec![("element", "b")]),
+                    (0, vec![("element", "c")]),
+                    (0, vec![("element", "d")]),
+                ]
+            );
+            assert_eq!(cursor.match_limit(), 32);
+        }
+
+        // The byte range is relative to the document, not to the node being queried.
+        let captures = cursor.captures(&query, inner_array, source.as_bytes());
         assert_eq!(
-            collect_matches(matches, &query, source),
-            &[
-                (1, vec![("left", "<"), ("right", ">")]),
-                (0, vec![("left", "{"), ("right", "}")]),
-            ]
+            collect_captures(captures, &query, source),
+            &[("element", "d")]
         );
 
-        // from within the type parameter list to within the function body
-        let start_offset = source.find("D: E>").unwrap();
-        let end_offset = source.find("g(f)").unwrap();
-        let matches = cursor.set_byte_range(start_offset..end_offset).matches(
-            &query,
-            tree.root_node(),
-            source.as_bytes(),
-        );
+        // Setting an empty range removes the limit.
+        cursor.set_byte_range(0..0);
+        let matches = cursor.matches(&query, inner_array, source.as_bytes());
         assert_eq!(
             collect_matches(matches, &query, source),
-            &[
-                (1, vec![("left", "<"), ("right", ">")]),
-                (0, vec![("left", "{"), ("right", "}")]),
-                (0, vec![("left", "{"), ("right", "}")]),
-            ]
+            &[(0, vec![("element", "d")]), (0, vec![("element", "e")])]
         );
     });
 }
 
 #[test]
-fn test_query_matches_with_wildcard_at_root_intersecting_byte_range() {
+fn test_query_cursor_cancellation() {
     allocations::record(|| {
-        let language = get_language("python");
-        let query = Query::new(
-            language,
-            "
-            [
-                (_ body: (block))
-                (_ consequence: (block))
-            ] @indent
-            ",
-        )
-        .unwrap();
-
-        let source = "
-            class A:
-                def b():
-                    if c:
-                        d
-                    else:
-                        e
-        "
-        .trim();
+        let language = get_language("javascript");
+        let query = Query::new(language, "(identifier) @element").unwrap();
 
+        let source = "a; b; c;\n".repeat(1000);
         let mut parser = Parser::new();
         parser.set_language(language).unwrap();
-        let tree = parser.parse(source, None).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
         let mut cursor = QueryCursor::new();
+        let flag = AtomicUsize::new(0);
+        unsafe { cursor.set_cancellation_flag(Some(&flag)) };
+        assert!(unsafe { cursor.cancellation_flag() }.is_some());
 
-        // After the first line of the class definition
-        let offset = source.find("A:").unwrap() + 2;
-        let matches = cursor
-            .set_byte_range(offset..offset)
+        let count = cursor
             .matches(&query, tree.root_node(), source.as_bytes())
-            .map(|mat| mat.captures[0].node.kind())
-            .collect::<Vec<_>>();
-        assert_eq!(matches, &["class_definition"]);
+            .count();
+        assert_eq!(count, 3000);
+
+        // The cursor halts soon after the flag is set.
+        flag.store(1, Ordering::SeqCst);
+        let count = cursor
+            .captures(&query, tree.root_node(), source.as_bytes())
+            .count();
+        assert!(count < 100);
+
+        flag.store(0, Ordering::SeqCst);
+        let mut count = 0;
+        for _ in cursor.matches(&query, tree.root_node(), source.as_bytes()) {
+            count += 1;
+            if count == 10 {
+                flag.store(1, Ordering::SeqCst);
+            }
+        }
+        assert!(count < 100);
 
-        // After the first line of the function definition
-        let offset = source.find("b():").unwrap() + 4;
-        let matches = cursor
-            .set_byte_range(offset..offset)
+        unsafe { cursor.set_cancellation_flag(None) };
+        assert!(unsafe { cursor.cancellation_flag() }.is_none());
+        let count = cursor
             .matches(&query, tree.root_node(), source.as_bytes())
-            .map(|mat| mat.captures[0].node.kind())
-            .collect::<Vec<_>>();
-        assert_eq!(matches, &["class_definition", "function_definition"]);
+            .count();
+        assert_eq!(count, 3000);
+    });
+}
 
-        // After the first line of the if statement
-        let offset = source.find("c:").unwrap() + 2;
-        let matches = cursor
-            .set_byte_range(offset..offset)
+#[test]
+fn test_query_matches_with_deadline() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(language, "(identifier) @element").unwrap();
+
+        let source = "a; b; c;\n".repeat(1000);
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // A distant deadline doesn't affect the results.
+        let deadline = Instant::now() + Duration::from_secs(3600);
+        let mut matches =
+            cursor.matches_with_deadline(&query, tree.root_node(), source.as_bytes(), deadline);
+        assert_eq!(matches.by_ref().count(), 3000);
+        assert!(!matches.did_exceed_deadline());
+
+        // A deadline that has already passed stops the iteration immediately.
+        let mut matches = cursor.matches_with_deadline(
+            &query,
+            tree.root_node(),
+            source.as_bytes(),
+            Instant::now(),
+        );
+        assert_eq!(matches.by_ref().count(), 0);
+        assert!(matches.did_exceed_deadline());
+
+        // The iteration stops shortly after the deadline passes.
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let mut count = 0;
+        let mut matches =
+            cursor.matches_with_deadline(&query, tree.root_node(), source.as_bytes(), deadline);
+        for _ in matches.by_ref() {
+            count += 1;
+            if count == 10 {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        }
+        assert!(count < 20);
+        assert!(matches.did_exceed_deadline());
+    });
+}
+
+#[test]
+fn test_query_matches_filtered() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(language, "(identifier) @element").unwrap();
+        let function_kind = language.id_for_node_kind("function_declaration", true);
+
+        let source = "
+            var a = 1;
+            function b(c) { return d; }
+            var e = function f() { return g; };
+            function h() {}
+        "
+        .unindent();
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let matches = cursor.matches_filtered(
+            &query,
+            tree.root_node(),
+            &[function_kind],
+            source.as_bytes(),
+        );
+        assert_eq!(
+            format_captures(
+                matches.iter().flat_map(|m| m.captures.iter().copied()),
+                &query,
+                &source
+            ),
+            &[
+                ("element", "b"),
+                ("element", "c"),
+                ("element", "d"),
+                ("element", "h"),
+            ]
+        );
+
+        let matches = cursor.matches_filtered(&query, tree.root_node(), &[], source.as_bytes());
+        assert!(matches.is_empty());
+    });
+}
+
+#[test]
+fn test_query_matches_in_changed_ranges() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(language, "(number) @number (string) @string").unwrap();
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let mut tree = parser.parse("var a = 1;\nvar b = 2;\n", None).unwrap();
+
+        // Replace the `2` with a string.
+        let source = "var a = 1;\nvar b = \"x\";\n";
+        let edit = InputEdit {
+            start_byte: 19,
+            old_end_byte: 20,
+            new_end_byte: 22,
+            start_position: Point::new(1, 8),
+            old_end_position: Point::new(1, 9),
+            new_end_position: Point::new(1, 11),
+        };
+        let new_tree = parser.reparse(&mut tree, &edit, source).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(0..10);
+        let matches = cursor.matches_in_changed_ranges(&query, &tree, &new_tree, source.as_bytes());
+        assert_eq!(
+            format_captures(
+                matches.iter().flat_map(|m| m.captures.iter().copied()),
+                &query,
+                source
+            ),
+            &[("string", "\"x\"")]
+        );
+
+        // The cursor's own byte range is left unchanged.
+        let matches = cursor.matches(&query, new_tree.root_node(), source.as_bytes());
+        assert_eq!(matches.count(), 1);
+
+        // Identical trees have no changed ranges, so there are no matches.
+        let same_tree = parser.parse(source, Some(&new_tree)).unwrap();
+        let matches =
+            cursor.matches_in_changed_ranges(&query, &new_tree, &same_tree, source.as_bytes());
+        assert!(matches.is_empty());
+    });
+}
+
+#[test]
+fn test_query_captures_within_byte_range() {
+    allocations::record(|| {
+        let language = get_language("c");
+        let query = Query::new(
+            language,
+            "
+            (call_expression
+                function: (identifier) @function
+                arguments: (argument_list (string_literal) @string.arg))
+
+            (string_literal) @string
+           ",
+        )
+        .unwrap();
+
+        let source = r#"DEFUN ("safe-length", Fsafe_length, Ssafe_length, 1, 1, 0)"#;
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        let captures =
+            cursor
+                .set_byte_range(3..27)
+                .captures(&query, tree.root_node(), source.as_bytes());
+
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("function", "DEFUN"),
+                ("string.arg", "\"safe-length\""),
+                ("string", "\"safe-length\""),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_query_matches_with_unrooted_patterns_intersecting_byte_range() {
+    allocations::record(|| {
+        let language = get_language("rust");
+        let query = Query::new(
+            language,
+            r#"
+            ("{" @left "}" @right)
+            ("<" @left ">" @right)
+            "#,
+        )
+        .unwrap();
+
+        let source = "mod a { fn a<B: C, D: E>(f: B) { g(f) } }";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // within the type parameter list
+        let offset = source.find("D: E>").unwrap();
+        let matches = cursor.set_byte_range(offset..offset).matches(
+            &query,
+            tree.root_node(),
+            source.as_bytes(),
+        );
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[
+                (1, vec![("left", "<"), ("right", ">")]),
+                (0, vec![("left", "{"), ("right", "}")]),
+            ]
+        );
+
+        // from within the type parameter list to within the function body
+        let start_offset = source.find("D: E>").unwrap();
+        let end_offset = source.find("g(f)").unwrap();
+        let matches = cursor.set_byte_range(start_offset..end_offset).matches(
+            &query,
+            tree.root_node(),
+            source.as_bytes(),
+        );
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[
+                (1, vec![("left", "<"), ("right", ">")]),
+                (0, vec![("left", "{"), ("right", "}")]),
+                (0, vec![("left", "{"), ("right", "}")]),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_query_matches_with_wildcard_at_root_intersecting_byte_range() {
+    allocations::record(|| {
+        let language = get_language("python");
+        let query = Query::new(
+            language,
+            "
+            [
+                (_ body: (block))
+                (_ consequence: (block))
+            ] @indent
+            ",
+        )
+        .unwrap();
+
+        let source = "
+            class A:
+                def b():
+                    if c:
+                        d
+                    else:
+                        e
+        "
+        .trim();
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // After the first line of the class definition
+        let offset = source.find("A:").unwrap() + 2;
+        let matches = cursor
+            .set_byte_range(offset..offset)
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .map(|mat| mat.captures[0].node.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(matches, &["class_definition"]);
+
+        // After the first line of the function definition
+        let offset = source.find("b():").unwrap() + 4;
+        let matches = cursor
+            .set_byte_range(offset..offset)
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .map(|mat| mat.captures[0].node.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(matches, &["class_definition", "function_definition"]);
+
+        // After the first line of the if statement
+        let offset = source.find("c:").unwrap() + 2;
+        let matches = cursor
+            .set_byte_range(offset..offset)
             .matches(&query, tree.root_node(), source.as_bytes())
             .map(|mat| mat.captures[0].node.kind())
             .collect::<Vec<_>>();
@@ -2384,6 +2921,102 @@ fn test_query_matches_with_multiple_captures_on_a_node() {
     });
 }
 
+#[test]
+fn test_query_match_capture_accessors() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            "(function_declaration (identifier) @name (statement_block) @body)",
+        )
+        .unwrap();
+
+        let source = "function foo() { return 1; }";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let m = matches.next().unwrap();
+        assert_eq!(m.capture_count(), 2);
+        assert_eq!(m.capture(0).unwrap().index, 0);
+        assert_eq!(
+            m.capture(0).unwrap().node.utf8_text(source.as_bytes()),
+            Ok("foo")
+        );
+        assert_eq!(m.capture(1).unwrap().index, 1);
+        assert_eq!(m.capture(1).unwrap().node.kind(), "statement_block");
+        assert!(m.capture(2).is_none());
+        assert_eq!(
+            m.captures_by_name(&query)
+                .map(|(name, node)| (name, node.kind()))
+                .collect::<Vec<_>>(),
+            &[("name", "identifier"), ("body", "statement_block")]
+        );
+        assert!(matches.next().is_none());
+    });
+}
+
+#[test]
+fn test_query_match_captures_in_pattern_order() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            (identifier) @variable
+
+            ; @call is written after @function and @args, but matched first
+            (call_expression
+                function: (identifier) @function
+                arguments: (arguments (_)* @args)) @call
+            "#,
+        )
+        .unwrap();
+
+        let source = "f(a, b);";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        let m = cursor
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .find(|m| m.pattern_index == 1)
+            .unwrap();
+        let names = |captures: &[QueryCapture]| {
+            captures
+                .iter()
+                .map(|c| {
+                    (
+                        query.capture_names()[c.index as usize].as_str(),
+                        c.node.utf8_text(source.as_bytes()).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(m.captures),
+            &[
+                ("call", "f(a, b)"),
+                ("function", "f"),
+                ("args", "a"),
+                ("args", "b")
+            ]
+        );
+        assert_eq!(
+            names(&m.captures_in_pattern_order(&query)),
+            &[
+                ("function", "f"),
+                ("args", "a"),
+                ("args", "b"),
+                ("call", "f(a, b)")
+            ]
+        );
+    });
+}
+
 #[test]
 fn test_query_matches_with_captured_wildcard_at_root() {
     allocations::record(|| {
@@ -2763,83 +3396,420 @@ fn test_query_captures_with_text_conditions() {
         )
         .unwrap();
 
-        let source = "
-          toad
-          load
-          panda
-          lambda
-          const ab = require('./ab');
-          new Cd(EF);
-        ";
-
+        let source = "
+          toad
+          load
+          panda
+          lambda
+          const ab = require('./ab');
+          new Cd(EF);
+        ";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("variable", "toad"),
+                ("variable", "panda"),
+                ("variable", "ab"),
+                ("function.builtin", "require"),
+                ("variable", "require"),
+                ("constructor", "Cd"),
+                ("variable", "Cd"),
+                ("constant", "EF"),
+                ("constructor", "EF"),
+                ("variable", "EF"),
+            ],
+        );
+    });
+}
+
+#[test]
+fn test_query_captures_with_case_insensitive_eq() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            ((identifier) @if
+             (#eq-ci? @if "iF"))
+
+            ((identifier) @not-if
+             (#not-eq-ci? @not-if "if")
+             (#match? @not-if "^[iI]"))
+
+            ((identifier) @summer
+             (#eq-ci? @summer "été"))
+
+            (assignment_expression
+              left: (identifier) @left
+              right: (identifier) @right
+              (#eq-ci? @left @right))
+            "#,
+        )
+        .unwrap();
+
+        let source = "
+          IF;
+          If;
+          it;
+          été;
+          ÉTÉ;
+          éTé;
+          Foo = foo;
+          Bar = baz;
+        ";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // Only ASCII letters are compared case-insensitively.
+        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("if", "IF"),
+                ("if", "If"),
+                ("not-if", "it"),
+                ("summer", "été"),
+                ("summer", "éTé"),
+                ("left", "Foo"),
+                ("right", "foo"),
+            ],
+        );
+    });
+}
+
+#[test]
+fn test_query_captures_with_predicates() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        let query = Query::new(
+            language,
+            r#"
+            ((call_expression (identifier) @foo)
+             (#set! name something)
+             (#set! cool)
+             (#something! @foo omg))
+
+            ((property_identifier) @bar
+             (#is? cool)
+             (#is-not? name something))"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.property_settings(0),
+            &[
+                QueryProperty::new("name", Some("something"), None),
+                QueryProperty::new("cool", None, None),
+            ]
+        );
+        assert_eq!(
+            query.general_predicates(0),
+            &[QueryPredicate {
+                operator: "something!".to_string().into_boxed_str(),
+                args: vec![
+                    QueryPredicateArg::Capture(0),
+                    QueryPredicateArg::String("omg".to_string().into_boxed_str()),
+                ],
+            },]
+        );
+        assert_eq!(query.property_settings(1), &[]);
+        assert_eq!(query.property_predicates(0), &[]);
+        assert_eq!(
+            query.property_predicates(1),
+            &[
+                (QueryProperty::new("cool", None, None), true),
+                (QueryProperty::new("name", Some("something"), None), false),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_query_pattern_predicates() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        let query = Query::new(
+            language,
+            r#"
+            (identifier)
+
+            ((call_expression (identifier) @foo (arguments) @bar)
+             (#eq? @foo "require")
+             (#match? @bar "^[(]")
+             (#set! name something)
+             (#something! @foo @bar omg))"#,
+        )
+        .unwrap();
+
+        assert_eq!(query.pattern_predicates(0).count(), 0);
+        assert_eq!(
+            query.pattern_predicates(1).collect::<Vec<_>>(),
+            &[
+                PredicateInfo {
+                    operator: "eq?".into(),
+                    args: vec![
+                        PredicateInfoArg::Capture {
+                            index: 0,
+                            name: "foo".into()
+                        },
+                        PredicateInfoArg::String("require".into()),
+                    ],
+                },
+                PredicateInfo {
+                    operator: "match?".into(),
+                    args: vec![
+                        PredicateInfoArg::Capture {
+                            index: 1,
+                            name: "bar".into()
+                        },
+                        PredicateInfoArg::String("^[(]".into()),
+                    ],
+                },
+                PredicateInfo {
+                    operator: "set!".into(),
+                    args: vec![
+                        PredicateInfoArg::String("name".into()),
+                        PredicateInfoArg::String("something".into()),
+                    ],
+                },
+                PredicateInfo {
+                    operator: "something!".into(),
+                    args: vec![
+                        PredicateInfoArg::Capture {
+                            index: 0,
+                            name: "foo".into()
+                        },
+                        PredicateInfoArg::Capture {
+                            index: 1,
+                            name: "bar".into()
+                        },
+                        PredicateInfoArg::String("omg".into()),
+                    ],
+                },
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_query_validate_captures() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            (identifier) @variable
+            (string) @string
+            (number) @nmber
+            (comment) @comment.doc
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.validate_captures(&["variable", "string", "number", "comment"]),
+            Err(vec!["nmber".to_string(), "comment.doc".to_string()])
+        );
+        assert_eq!(
+            query.validate_captures(&["variable", "string", "nmber", "comment.doc"]),
+            Ok(())
+        );
+    });
+}
+
+#[test]
+fn test_query_debug() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            (identifier) @id
+            ((call_expression function: (identifier) @fn) @call
+             (#eq? @fn "require")
+             (#set! kind import))"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            format!("{:?}", query),
+            concat!(
+                r#"Query { pattern_count: 2, capture_names: ["id", "fn", "call"], "#,
+                r#"predicates: [[], [(#eq? @fn "require"), (#set! "kind" "import")]] }"#,
+            )
+        );
+    });
+}
+
+#[test]
+fn test_query_match_display() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            "(call_expression
+                function: (identifier) @fn
+                arguments: (arguments (identifier) @arg))",
+        )
+        .unwrap();
+
+        let source = "a(b)";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+        let matches = cursor
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .map(|m| (m.to_string(), m.captures[1].to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            &[(
+                "pattern 0, 2 captures: [@0 identifier (0, 0) - (0, 1), @1 identifier (0, 2) - (0, 3)]"
+                    .to_string(),
+                "@1 identifier (0, 2) - (0, 3)".to_string()
+            )]
+        );
+    });
+}
+
+#[test]
+fn test_query_matches_raw() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            ((identifier) @id
+             (#eq? @id "require"))"#,
+        )
+        .unwrap();
+
+        let source = "const a = require('a'); b(a);";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("id", "require")])],
+        );
+
+        let matches = cursor.matches_raw(&query, tree.root_node());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[
+                (0, vec![("id", "a")]),
+                (0, vec![("id", "require")]),
+                (0, vec![("id", "b")]),
+                (0, vec![("id", "a")]),
+            ],
+        );
+    });
+}
+
+#[test]
+fn test_query_matches_with_predicate_filter() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            ((identifier) @name
+             (#has-ancestor? @name class_declaration))
+            (class_declaration) @class
+            "#,
+        )
+        .unwrap();
+
+        let source = "class A { b() { c; } } d;";
         let mut parser = Parser::new();
         parser.set_language(language).unwrap();
-        let tree = parser.parse(&source, None).unwrap();
+        let tree = parser.parse(source, None).unwrap();
         let mut cursor = QueryCursor::new();
+        let matches = cursor.matches_with_predicate_filter(
+            &query,
+            tree.root_node(),
+            source.as_bytes(),
+            |m, predicate| {
+                assert_eq!(predicate.operator.as_ref(), "has-ancestor?");
+                let ancestor_kind = match &predicate.args[1] {
+                    QueryPredicateArg::String(kind) => kind.as_ref(),
+                    _ => unreachable!(),
+                };
+                let mut node = m.captures[0].node;
+                while let Some(parent) = node.parent() {
+                    if parent.kind() == ancestor_kind {
+                        return true;
+                    }
+                    node = parent;
+                }
+                false
+            },
+        );
 
-        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
         assert_eq!(
-            collect_captures(captures, &query, source),
+            collect_matches(matches, &query, source),
             &[
-                ("variable", "toad"),
-                ("variable", "panda"),
-                ("variable", "ab"),
-                ("function.builtin", "require"),
-                ("variable", "require"),
-                ("constructor", "Cd"),
-                ("variable", "Cd"),
-                ("constant", "EF"),
-                ("constructor", "EF"),
-                ("variable", "EF"),
+                (1, vec![("class", "class A { b() { c; } }")]),
+                (0, vec![("name", "A")]),
+                (0, vec![("name", "c")]),
             ],
         );
     });
 }
 
 #[test]
-fn test_query_captures_with_predicates() {
+fn test_query_property_settings_with_one_or_two_arguments() {
     allocations::record(|| {
         let language = get_language("javascript");
 
         let query = Query::new(
             language,
             r#"
-            ((call_expression (identifier) @foo)
-             (#set! name something)
-             (#set! cool)
-             (#something! @foo omg))
-
-            ((property_identifier) @bar
-             (#is? cool)
-             (#is-not? name something))"#,
+            ((identifier) @foo
+             (#set! conceal)
+             (#set! "injection.language" "rust")
+             (#set! @foo local.definition)
+             (#set! @foo priority "105"))"#,
         )
         .unwrap();
 
         assert_eq!(
             query.property_settings(0),
             &[
-                QueryProperty::new("name", Some("something"), None),
-                QueryProperty::new("cool", None, None),
+                QueryProperty::new("conceal", None, None),
+                QueryProperty::new("injection.language", Some("rust"), None),
+                QueryProperty::new("local.definition", None, Some(0)),
+                QueryProperty::new("priority", Some("105"), Some(0)),
             ]
         );
+
         assert_eq!(
-            query.general_predicates(0),
-            &[QueryPredicate {
-                operator: "something!".to_string().into_boxed_str(),
-                args: vec![
-                    QueryPredicateArg::Capture(0),
-                    QueryPredicateArg::String("omg".to_string().into_boxed_str()),
-                ],
-            },]
+            Query::new(language, "((identifier) @foo (#set! @foo))")
+                .unwrap_err()
+                .message,
+            "Invalid arguments to set! predicate. Missing key argument"
         );
-        assert_eq!(query.property_settings(1), &[]);
-        assert_eq!(query.property_predicates(0), &[]);
         assert_eq!(
-            query.property_predicates(1),
-            &[
-                (QueryProperty::new("cool", None, None), true),
-                (QueryProperty::new("name", Some("something"), None), false),
-            ]
+            Query::new(language, "((identifier) (#set! a b c d))")
+                .unwrap_err()
+                .message,
+            "Wrong number of arguments to set! predicate. Expected 1 to 3, got 4."
         );
     });
 }
@@ -2924,6 +3894,224 @@ fn test_query_captures_with_duplicates() {
     });
 }
 
+#[test]
+fn test_query_captures_deduped() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            (call_expression
+                function: (identifier) @function)
+
+            (identifier) @variable
+
+            (call_expression) @call
+            "#,
+        )
+        .unwrap();
+
+        let source = "a(b);";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("call", "a(b)"),
+                ("function", "a"),
+                ("variable", "a"),
+                ("variable", "b"),
+            ],
+        );
+
+        // Only the capture from the earliest pattern is kept for each node.
+        let captures = cursor.captures_deduped(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[("call", "a(b)"), ("function", "a"), ("variable", "b")],
+        );
+    });
+}
+
+#[test]
+fn test_query_captures_by_priority() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            (call_expression
+                function: (identifier) @function)
+
+            (identifier) @variable
+
+            (call_expression) @call
+            "#,
+        )
+        .unwrap();
+
+        let source = "a(b);";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // The call is only reported for the bytes that no earlier pattern captures.
+        let captures = cursor
+            .captures_by_priority(&query, tree.root_node(), source.as_bytes())
+            .map(|(pattern_index, capture, range)| {
+                (
+                    pattern_index,
+                    query.capture_names()[capture.index as usize].as_str(),
+                    &source[range],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            captures,
+            &[
+                (0, "function", "a"),
+                (2, "call", "("),
+                (1, "variable", "b"),
+                (2, "call", ")"),
+            ],
+        );
+    });
+}
+
+#[test]
+fn test_highlight() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            (call_expression
+                function: (identifier) @function)
+
+            (identifier) @variable
+
+            (arguments) @punctuation
+
+            (string) @string
+            "#,
+        )
+        .unwrap();
+
+        let source = "a(b, \"c\");";
+
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        let spans = highlight(&tree, &query, source.as_bytes());
+        assert_eq!(
+            spans
+                .iter()
+                .map(|(range, capture_index)| (
+                    &source[range.start_byte..range.end_byte],
+                    query.capture_names()[*capture_index].as_str(),
+                ))
+                .collect::<Vec<_>>(),
+            &[
+                ("a", "function"),
+                ("(", "punctuation"),
+                ("b", "variable"),
+                (", ", "punctuation"),
+                ("\"c\"", "string"),
+                (")", "punctuation"),
+            ]
+        );
+        assert_eq!(spans[4].0.start_point, Point::new(0, 5));
+        assert_eq!(spans[4].0.end_point, Point::new(0, 8));
+    });
+}
+
+#[test]
+fn test_query_captures_with_text() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            "(call_expression
+                function: (identifier) @fn
+                arguments: (arguments (identifier) @arg))",
+        )
+        .unwrap();
+
+        let source = "a(bb); ccc(dddd);";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let captures = cursor
+            .captures_with_text(&query, tree.root_node(), source.as_bytes())
+            .map(|(pattern_index, capture, text)| {
+                assert!(matches!(text, Cow::Borrowed(_)));
+                (pattern_index, capture.index, text.into_owned())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            captures,
+            &[
+                (0, 0, b"a".to_vec()),
+                (0, 1, b"bb".to_vec()),
+                (0, 0, b"ccc".to_vec()),
+                (0, 1, b"dddd".to_vec()),
+            ]
+        );
+
+        // Text that is split across multiple chunks is concatenated.
+        let chunks = source.as_bytes().chunks(3).collect::<Vec<_>>();
+        let chunked_provider = |node: Node| {
+            let range = node.byte_range();
+            chunks.iter().enumerate().filter_map(move |(i, chunk)| {
+                let offset = i * 3;
+                let start = range.start.max(offset);
+                let end = range.end.min(offset + chunk.len());
+                if start < end {
+                    Some(&chunk[(start - offset)..(end - offset)])
+                } else {
+                    None
+                }
+            })
+        };
+        let texts = cursor
+            .captures_with_text(&query, tree.root_node(), chunked_provider)
+            .map(|(_, _, text)| text.into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            &[
+                b"a".to_vec(),
+                b"bb".to_vec(),
+                b"ccc".to_vec(),
+                b"dddd".to_vec()
+            ]
+        );
+
+        // Text that was resolved to evaluate a predicate is not requested again.
+        let query = Query::new(language, r#"((identifier) @id (#not-eq? @id "a"))"#).unwrap();
+        let mut requested = Vec::new();
+        let texts = cursor
+            .captures_with_text(&query, tree.root_node(), |node: Node| {
+                requested.push(node.byte_range());
+                iter::once(&source.as_bytes()[node.byte_range()])
+            })
+            .map(|(_, _, text)| text.into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, &[b"bb".to_vec(), b"ccc".to_vec(), b"dddd".to_vec()]);
+        assert_eq!(requested, &[0..1, 2..4, 7..10, 11..15]);
+    });
+}
+
 #[test]
 fn test_query_captures_with_many_nested_results_without_fields() {
     allocations::record(|| {
@@ -3436,6 +4624,59 @@ fn test_query_text_callback_returns_chunks() {
     });
 }
 
+#[test]
+fn test_query_text_callback_returns_owned_chunks() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            language,
+            r#"
+            ((identifier) @constant
+             (#match? @constant "^[A-Z][A-Z_]*$"))
+            ((identifier) @keyword
+             (#eq? @keyword "transform"))
+            "#,
+        )
+        .unwrap();
+
+        let source = "SOMETHING[a] = transform(b, PARAMETER);";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        // Materialize each node's text on demand, as a source that is not stored in
+        // a flat buffer would have to.
+        let mut cursor = QueryCursor::new();
+        let captures = cursor.captures(&query, tree.root_node(), |node: Node| {
+            source.as_bytes()[node.byte_range()]
+                .chunks(2)
+                .map(|chunk| chunk.to_vec())
+                .collect::<Vec<_>>()
+                .into_iter()
+        });
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("constant", "SOMETHING"),
+                ("keyword", "transform"),
+                ("constant", "PARAMETER"),
+            ]
+        );
+
+        let node = tree.root_node().descendant_for_byte_range(15, 15).unwrap();
+        assert_eq!(
+            node.text_with_provider(|node: Node| {
+                std::iter::once(source[node.byte_range()].to_uppercase().into_bytes())
+            }),
+            b"TRANSFORM"
+        );
+        let text = node.text_cow(|node: Node| {
+            std::iter::once(source[node.byte_range()].to_uppercase().into_bytes())
+        });
+        assert!(matches!(text, Cow::Owned(text) if text == b"TRANSFORM"));
+    });
+}
+
 #[test]
 fn test_query_start_byte_for_pattern() {
     let language = get_language("javascript");
@@ -3643,6 +4884,43 @@ fn test_query_disable_pattern() {
     });
 }
 
+#[test]
+fn test_query_clone() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let mut query = Query::new(
+            language,
+            r#"
+                (function_declaration
+                    name: (identifier) @name)
+                ((class_declaration
+                    name: (identifier) @name
+                    body: (class_body) @body)
+                 (#eq? @name "A"))
+            "#,
+        )
+        .unwrap();
+        query.disable_capture("body");
+        query.disable_pattern(0);
+
+        let clone = query.clone();
+        assert_eq!(clone.capture_names(), query.capture_names());
+        assert_eq!(clone.pattern_count(), query.pattern_count());
+        drop(query);
+
+        let source = "class A {} class B {} function c() {}";
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+        let matches = cursor.matches(&clone, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &clone, source),
+            &[(1, vec![("name", "A")])],
+        );
+    });
+}
+
 #[test]
 fn test_query_alternative_predicate_prefix() {
     allocations::record(|| {
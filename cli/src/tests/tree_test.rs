@@ -2,7 +2,9 @@ use super::helpers::edits::invert_edit;
 use super::helpers::fixtures::get_language;
 use crate::parse::{perform_edit, Edit};
 use std::str;
-use tree_sitter::{InputEdit, Parser, Point, Range, Tree};
+use tree_sitter::{
+    EditOverlap, InputEdit, Node, ParsedDocument, Parser, Point, Range, Tree, TreeStats,
+};
 
 #[test]
 fn test_tree_edit() {
@@ -232,6 +234,48 @@ fn test_tree_edit() {
     }
 }
 
+#[test]
+fn test_tree_edit_all() {
+    let text = b"a;\nb;\nc;";
+    let (edits, new_text) = InputEdit::from_changes(text, &[(6..7, "z"), (3..4, "xx\nyy")]);
+    assert_eq!(new_text, b"a;\nxx\nyy;\nz;");
+    assert_eq!(
+        edits,
+        &[
+            InputEdit {
+                start_byte: 3,
+                old_end_byte: 4,
+                new_end_byte: 8,
+                start_position: Point::new(1, 0),
+                old_end_position: Point::new(1, 1),
+                new_end_position: Point::new(2, 2),
+            },
+            InputEdit {
+                start_byte: 10,
+                old_end_byte: 11,
+                new_end_byte: 11,
+                start_position: Point::new(3, 0),
+                old_end_position: Point::new(3, 1),
+                new_end_position: Point::new(3, 1),
+            },
+        ]
+    );
+
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let mut tree = parser.parse(text, None).unwrap();
+    tree.edit_all(&edits);
+    let last_statement = tree.root_node().child(2).unwrap();
+    assert_eq!(last_statement.start_position(), Point::new(3, 0));
+    assert_eq!(last_statement.byte_range(), 10..12);
+
+    let new_tree = parser.parse(&new_text, Some(&tree)).unwrap();
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        parser.parse(&new_text, None).unwrap().root_node().to_sexp()
+    );
+}
+
 #[test]
 fn test_tree_edit_with_included_ranges() {
     let mut parser = Parser::new();
@@ -331,6 +375,18 @@ fn test_tree_cursor() {
     assert!(cursor.goto_next_sibling());
     assert_eq!(cursor.node().kind(), "field_declaration_list");
     assert_eq!(cursor.node().is_named(), true);
+
+    assert!(cursor.goto_parent());
+    assert_eq!(cursor.node().kind(), "struct_item");
+
+    assert!(cursor.goto_last_child());
+    assert_eq!(cursor.node().kind(), "field_declaration_list");
+
+    assert!(cursor.goto_parent());
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.node().kind(), "struct");
+    assert!(!cursor.goto_last_child());
+    assert_eq!(cursor.node().kind(), "struct");
 }
 
 #[test]
@@ -489,6 +545,451 @@ fn test_tree_node_equality() {
     assert_ne!(node1.child(0).unwrap(), node2);
 }
 
+#[test]
+fn test_tree_node_ids_after_clone() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("rust")).unwrap();
+    let tree = parser.parse("struct A { b: C }", None).unwrap();
+    let clone = tree.clone();
+
+    // The root node's id belongs to the tree itself.
+    assert_ne!(tree.root_node().id(), clone.root_node().id());
+
+    // All of the other nodes are shared between the tree and its clone.
+    let mut cursor = tree.walk();
+    let mut clone_cursor = clone.walk();
+    assert!(cursor.goto_first_child());
+    assert!(clone_cursor.goto_first_child());
+    loop {
+        assert_eq!(cursor.node().id(), clone_cursor.node().id());
+        assert_eq!(cursor.node(), clone_cursor.node());
+        if cursor.goto_first_child() {
+            assert!(clone_cursor.goto_first_child());
+            continue;
+        }
+        assert!(!clone_cursor.goto_first_child());
+        while !cursor.goto_next_sibling() {
+            assert!(!clone_cursor.goto_next_sibling());
+            if !cursor.goto_parent() {
+                return;
+            }
+            assert!(clone_cursor.goto_parent());
+        }
+        assert!(clone_cursor.goto_next_sibling());
+    }
+}
+
+#[test]
+fn test_node_same_tree() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("rust")).unwrap();
+    let tree1 = parser.parse("struct A {}", None).unwrap();
+    let tree2 = parser.parse("struct A {}", None).unwrap();
+    let clone = tree1.clone();
+
+    let node1 = tree1.root_node().child(0).unwrap();
+    assert!(node1.same_tree(&tree1.root_node()));
+    assert!(!node1.same_tree(&tree2.root_node().child(0).unwrap()));
+
+    // Nodes from a clone are equal, but do not belong to the same tree.
+    let clone_node = clone.root_node().child(0).unwrap();
+    assert_eq!(node1, clone_node);
+    assert!(!node1.same_tree(&clone_node));
+}
+
+#[test]
+fn test_tree_structural_equality() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let mut source_code = b"let a = [1, 2];\nfoo(a);\n".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+
+    assert!(tree.structurally_eq(&tree.clone()));
+
+    // An incremental reparse produces the same structure as a fresh parse.
+    let edit = Edit {
+        position: index_of(&source_code, "2"),
+        deleted_length: 0,
+        inserted_text: b"3, ".to_vec(),
+    };
+    perform_edit(&mut tree, &mut source_code, &edit);
+    let incremental_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    let fresh_tree = parser.parse(&source_code, None).unwrap();
+    assert!(incremental_tree.structurally_eq(&fresh_tree));
+    assert!(fresh_tree.structurally_eq(&incremental_tree));
+
+    // Differences in node kinds, children, or ranges are detected.
+    let other_tree = parser.parse("let a = [1, 3, 2];\nbar(a);\n", None).unwrap();
+    assert!(fresh_tree.structurally_eq(&other_tree));
+    let other_tree = parser.parse("let a = [1, 3];\nfoo(a);\n", None).unwrap();
+    assert!(!fresh_tree.structurally_eq(&other_tree));
+    let other_tree = parser
+        .parse("let a = [1, 3, 2];\nfoo(a, b);\n", None)
+        .unwrap();
+    assert!(!fresh_tree.structurally_eq(&other_tree));
+    let other_tree = parser
+        .parse(" let a = [1, 3, 2];\nfoo(a);\n", None)
+        .unwrap();
+    assert!(!fresh_tree.structurally_eq(&other_tree));
+}
+
+#[test]
+fn test_node_overlaps_edit() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let source = "abc + def * ghi;";
+    let tree = parser.parse(source, None).unwrap();
+    let node_at = |text: &str| {
+        let start = source.find(text).unwrap();
+        tree.root_node()
+            .descendant_for_byte_range(start, start + text.len())
+            .unwrap()
+    };
+
+    // Replace "ef * g" with "x".
+    let edit = InputEdit {
+        start_byte: 7,
+        old_end_byte: 13,
+        new_end_byte: 8,
+        start_position: Point::new(0, 7),
+        old_end_position: Point::new(0, 13),
+        new_end_position: Point::new(0, 8),
+    };
+    assert_eq!(node_at("abc").overlaps_edit(&edit), EditOverlap::Before);
+    assert_eq!(node_at("def").overlaps_edit(&edit), EditOverlap::Straddles);
+    assert_eq!(node_at("*").overlaps_edit(&edit), EditOverlap::Contained);
+    assert_eq!(node_at("ghi").overlaps_edit(&edit), EditOverlap::Straddles);
+    assert_eq!(
+        node_at("def * ghi").overlaps_edit(&edit),
+        EditOverlap::Contains
+    );
+    assert_eq!(node_at(";").overlaps_edit(&edit), EditOverlap::After);
+
+    // An insertion at the boundary between two nodes overlaps neither of them.
+    let edit = InputEdit {
+        start_byte: 3,
+        old_end_byte: 3,
+        new_end_byte: 4,
+        start_position: Point::new(0, 3),
+        old_end_position: Point::new(0, 3),
+        new_end_position: Point::new(0, 4),
+    };
+    assert_eq!(node_at("abc").overlaps_edit(&edit), EditOverlap::Before);
+    assert_eq!(node_at("+").overlaps_edit(&edit), EditOverlap::After);
+    assert_eq!(
+        node_at("abc + def * ghi").overlaps_edit(&edit),
+        EditOverlap::Contains
+    );
+}
+
+#[test]
+fn test_node_changed_descendants() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let mut source_code = b"let a = [1, 2];\nfoo(bar);\nlet c = 3;\n".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+    assert_eq!(tree.root_node().changed_descendants().count(), 0);
+
+    let edit = Edit {
+        position: index_of(&source_code, "bar"),
+        deleted_length: 3,
+        inserted_text: b"baz".to_vec(),
+    };
+    perform_edit(&mut tree, &mut source_code, &edit);
+
+    let changed_nodes = tree.root_node().changed_descendants().collect::<Vec<_>>();
+    assert!(changed_nodes.iter().all(|node| node.has_changes()));
+    assert!(changed_nodes
+        .iter()
+        .any(|node| node.kind() == "identifier" && node.start_byte() == edit.position));
+
+    // Shallower nodes come first, and unchanged subtrees are skipped.
+    let first_node = changed_nodes[0];
+    assert_eq!(first_node.kind(), "expression_statement");
+    assert!(!changed_nodes
+        .iter()
+        .any(|node| node.kind() == "lexical_declaration"));
+
+    let mut all_changed_nodes = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.has_changes() {
+                all_changed_nodes.push(child);
+                stack.push(child);
+            }
+        }
+    }
+    assert_eq!(changed_nodes.len(), all_changed_nodes.len());
+}
+
+#[test]
+fn test_range_merge_all() {
+    let text = b"abc def ghi jkl".to_vec();
+
+    assert_eq!(Range::merge_all(vec![]), vec![]);
+    assert_eq!(
+        Range::merge_all(vec![
+            range_of(&text, "jkl"),
+            // Overlapping ranges are combined
+            range_of(&text, "c def"),
+            range_of(&text, "abc"),
+            // Touching ranges are combined
+            range_of(&text, " g"),
+            // Contained ranges are absorbed
+            range_of(&text, "de"),
+        ]),
+        vec![range_of(&text, "abc def g"), range_of(&text, "jkl")]
+    );
+}
+
+#[test]
+fn test_point_comparison_with_tuples() {
+    let point = Point::new(3, 5);
+    assert_eq!(point, (3, 5));
+    assert_eq!((3, 5), point);
+    assert_ne!(point, (5, 3));
+    assert!(point < (3, 6));
+    assert!(point < (4, 0));
+    assert!(point > (2, 10));
+    assert!(point >= (3, 5));
+    assert!((10, 0) > point);
+    assert!((3, 4) < point);
+}
+
+#[test]
+fn test_point_saturating_sub_and_clamp() {
+    let point = Point::new(3, 5);
+    assert_eq!(point.saturating_sub(Point::new(1, 8)), Point::new(2, 5));
+    assert_eq!(point.saturating_sub(Point::new(3, 2)), Point::new(0, 3));
+    assert_eq!(point.saturating_sub(Point::new(0, 0)), point);
+    assert_eq!(point.saturating_sub(point), Point::new(0, 0));
+    assert_eq!(point.saturating_sub(Point::new(3, 6)), Point::new(0, 0));
+    assert_eq!(point.saturating_sub(Point::new(4, 0)), Point::new(0, 0));
+
+    assert_eq!(point.clamp_to(Point::new(3, 2)), Point::new(3, 2));
+    assert_eq!(point.clamp_to(Point::new(2, 10)), Point::new(2, 10));
+    assert_eq!(point.clamp_to(Point::new(4, 0)), point);
+}
+
+#[test]
+fn test_range_constructors() {
+    let range = Range::new(4, 7, Point::new(0, 4), Point::new(0, 7));
+    assert_eq!(range, range_of(&b"abc def ghi".to_vec(), "def"));
+
+    let empty = Range::empty_at(10, Point::new(1, 2));
+    assert_eq!(empty.start_byte, 10);
+    assert_eq!(empty.end_byte, 10);
+    assert_eq!(empty.point_range(), Point::new(1, 2)..Point::new(1, 2));
+
+    assert_eq!(
+        Range::new(0, 42, Point::new(1, 0), Point::new(3, 5)).to_string(),
+        "0..42 (1, 0)-(3, 5)"
+    );
+    assert_eq!(empty.to_string(), "10..10 (1, 2)-(1, 2)");
+
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let tree = parser.parse("a;\nb;", None).unwrap();
+    let node = tree.root_node().child(1).unwrap();
+    assert_eq!(Range::from_node(&node), node.range());
+    assert_eq!(
+        Range::from_node(&node),
+        Range::new(3, 5, Point::new(1, 0), Point::new(1, 2))
+    );
+}
+
+#[test]
+fn test_tree_errors() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("c")).unwrap();
+
+    let tree = parser.parse("int a = 1;\nint b = 2;\n", None).unwrap();
+    assert!(tree.errors().is_empty());
+
+    let tree = parser.parse("int a = 1;\nint b = 2\n", None).unwrap();
+    let errors = tree.errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].is_missing());
+    assert_eq!(errors[0].kind(), ";");
+    assert_eq!(errors[0].start_position(), Point::new(1, 9));
+
+    let tree = parser.parse("int a = 1;\n@@ int b = 2;\n", None).unwrap();
+    let errors = tree.errors();
+    assert!(errors[0].is_error());
+    assert_eq!(errors[0].start_position(), Point::new(1, 0));
+    assert!(errors.iter().all(|node| node.start_position().row == 1));
+}
+
+#[test]
+fn test_tree_size_bytes() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+
+    let small_tree = parser.parse("a(b);", None).unwrap();
+    let large_tree = parser.parse("a(b);\n".repeat(100), None).unwrap();
+    assert!(small_tree.size_bytes() > 0);
+    assert!(large_tree.size_bytes() > 50 * small_tree.size_bytes());
+
+    // A clone shares its nodes with the original, but reports the same size.
+    assert_eq!(large_tree.clone().size_bytes(), large_tree.size_bytes());
+}
+
+#[test]
+fn test_tree_stats() {
+    fn collect_stats(node: Node, depth: usize, stats: &mut TreeStats) {
+        stats.node_count += 1;
+        stats.named_node_count += node.is_named() as usize;
+        stats.error_count += (node.is_error() || node.is_missing()) as usize;
+        stats.max_depth = stats.max_depth.max(depth);
+        for child in node.children(&mut node.walk()) {
+            collect_stats(child, depth + 1, stats);
+        }
+    }
+
+    let mut parser = Parser::new();
+    parser.set_language(get_language("c")).unwrap();
+
+    let tree = parser.parse("int a = 1;\nint b = 2\n", None).unwrap();
+    let stats = tree.stats();
+    assert_eq!(stats.error_count, 1);
+    assert_eq!(stats.byte_size, tree.root_node().end_byte());
+
+    let mut expected_stats = TreeStats {
+        byte_size: stats.byte_size,
+        ..TreeStats::default()
+    };
+    collect_stats(tree.root_node(), 0, &mut expected_stats);
+    assert_eq!(stats, expected_stats);
+}
+
+#[test]
+fn test_tree_kinds_present() {
+    let mut parser = Parser::new();
+    let language = get_language("rust");
+    parser.set_language(language).unwrap();
+
+    let tree = parser.parse("struct A { b: C }", None).unwrap();
+    let mut kinds = tree
+        .kinds_present()
+        .into_iter()
+        .map(|id| language.node_kind_for_id(id).unwrap())
+        .collect::<Vec<_>>();
+    kinds.sort_unstable();
+    assert_eq!(
+        kinds,
+        &[
+            ":",
+            "field_declaration",
+            "field_declaration_list",
+            "field_identifier",
+            "source_file",
+            "struct",
+            "struct_item",
+            "type_identifier",
+            "{",
+            "}",
+        ]
+    );
+}
+
+#[test]
+fn test_parsed_document() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+
+    let document = ParsedDocument::parse(&mut parser, "let x = 'é';").unwrap();
+    let declarator_node = document
+        .root_node()
+        .child(0)
+        .unwrap()
+        .named_child(0)
+        .unwrap();
+    assert_eq!(document.node_text(declarator_node), "x = 'é'".as_bytes());
+    let value_node = declarator_node.child_by_field_name("value").unwrap();
+    assert_eq!(document.node_utf8_text(value_node), Ok("'é'"));
+
+    // A reparse of different source produces a separate document.
+    let other_document = ParsedDocument::parse(&mut parser, String::from("f(y);")).unwrap();
+    assert_eq!(
+        other_document.node_utf8_text(other_document.root_node()),
+        Ok("f(y);")
+    );
+    assert_eq!(document.node_utf8_text(value_node), Ok("'é'"));
+
+    let (tree, source) = document.into_parts();
+    assert_eq!(source, "let x = 'é';".as_bytes());
+    assert_eq!(tree.root_node().kind(), "program");
+}
+
+#[test]
+fn test_tree_fold_ranges() {
+    let mut parser = Parser::new();
+    let language = get_language("json");
+    parser.set_language(language).unwrap();
+
+    let source = "[\n  {\n    \"a\": [1]\n  },\n  [\n    2\n  ]\n]";
+    let tree = parser.parse(source, None).unwrap();
+    let array_node = tree.root_node().child(0).unwrap();
+    assert_eq!(array_node.fold_range(), (0, 7));
+    assert_eq!(array_node.named_child(0).unwrap().fold_range(), (1, 3));
+
+    let kinds = [
+        language.id_for_node_kind("array", true),
+        language.id_for_node_kind("object", true),
+    ];
+    assert_eq!(tree.fold_ranges(&kinds), &[(0, 7), (1, 3), (4, 6)]);
+    assert!(tree.fold_ranges(&[]).is_empty());
+}
+
+#[test]
+#[cfg(feature = "tree-json")]
+fn test_tree_to_json() {
+    use serde_json::json;
+
+    let mut parser = Parser::new();
+    parser.set_language(get_language("python")).unwrap();
+    let tree = parser.parse("x = 1", None).unwrap();
+    let json = tree.to_json();
+
+    fn leaf(kind: &str, named: bool, field: Option<&str>, start: usize) -> serde_json::Value {
+        let end = start + if named { 1 } else { kind.len() };
+        json!({
+            "kind": kind,
+            "named": named,
+            "field": field,
+            "start_byte": start,
+            "end_byte": end,
+            "start_point": { "row": 0, "column": start },
+            "end_point": { "row": 0, "column": end },
+            "children": [],
+        })
+    }
+
+    assert_eq!(json["kind"], "module");
+    assert_eq!(json["field"], serde_json::Value::Null);
+    assert_eq!(json["end_byte"], 5);
+    assert_eq!(json["children"][0]["kind"], "expression_statement");
+    assert_eq!(
+        json["children"][0]["children"][0],
+        json!({
+            "kind": "assignment",
+            "named": true,
+            "field": null,
+            "start_byte": 0,
+            "end_byte": 5,
+            "start_point": { "row": 0, "column": 0 },
+            "end_point": { "row": 0, "column": 5 },
+            "children": [
+                leaf("identifier", true, Some("left"), 0),
+                leaf("=", false, None, 2),
+                leaf("integer", true, Some("right"), 4),
+            ],
+        })
+    );
+}
+
 #[test]
 fn test_get_changed_ranges() {
     let source_code = b"{a: null};\n".to_vec();
@@ -596,6 +1097,43 @@ fn test_get_changed_ranges() {
     }
 }
 
+#[test]
+fn test_get_changed_ranges_len() {
+    let mut parser = Parser::new();
+    parser.set_language(get_language("javascript")).unwrap();
+    let mut source_code = b"[a, b, c];\nfoo(d);".to_vec();
+    let mut tree = parser.parse(&source_code, None).unwrap();
+
+    // Identical trees have no changed ranges.
+    let new_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    let ranges = tree.changed_ranges(&new_tree);
+    assert_eq!(ranges.len(), 0);
+    assert_eq!(ranges.count(), 0);
+
+    // The length is accurate before iterating, and shrinks as ranges are yielded.
+    let position = index_of(&source_code, "b");
+    perform_edit(
+        &mut tree,
+        &mut source_code,
+        &Edit {
+            position,
+            deleted_length: 1,
+            inserted_text: b"(b)".to_vec(),
+        },
+    );
+    let new_tree = parser.parse(&source_code, Some(&tree)).unwrap();
+    let expected_count = tree.changed_ranges(&new_tree).count();
+    assert!(expected_count > 0);
+
+    let mut ranges = tree.changed_ranges(&new_tree);
+    for remaining in (0..=expected_count).rev() {
+        assert_eq!(ranges.len(), remaining);
+        assert_eq!(ranges.size_hint(), (remaining, Some(remaining)));
+        assert_eq!(ranges.next().is_some(), remaining > 0);
+    }
+    assert_eq!(ranges.len(), 0);
+}
+
 fn index_of(text: &Vec<u8>, substring: &str) -> usize {
     str::from_utf8(text.as_slice())
         .unwrap()
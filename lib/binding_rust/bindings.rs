@@ -1,5 +1,6 @@
 /* automatically generated by rust-bindgen 0.59.2 */
 
+pub type TSStateId = u16;
 pub type TSSymbol = u16;
 pub type TSFieldId = u16;
 #[repr(C)]
@@ -27,6 +28,11 @@ pub struct TSQuery {
 pub struct TSQueryCursor {
     _unused: [u8; 0],
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSLookaheadIterator {
+    _unused: [u8; 0],
+}
 pub const TSInputEncoding_TSInputEncodingUTF8: TSInputEncoding = 0;
 pub const TSInputEncoding_TSInputEncodingUTF16: TSInputEncoding = 1;
 pub type TSInputEncoding = ::std::os::raw::c_uint;
@@ -62,6 +68,19 @@ pub struct TSInput {
     >,
     pub encoding: TSInputEncoding,
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSParseState {
+    pub payload: *mut ::std::os::raw::c_void,
+    pub current_byte_offset: u32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSParseOptions {
+    pub payload: *mut ::std::os::raw::c_void,
+    pub progress_callback:
+        ::std::option::Option<unsafe extern "C" fn(state: *mut TSParseState) -> bool>,
+}
 pub const TSLogType_TSLogTypeParse: TSLogType = 0;
 pub const TSLogType_TSLogTypeLex: TSLogType = 1;
 pub type TSLogType = ::std::os::raw::c_uint;
@@ -239,6 +258,21 @@ extern "C" {
         input: TSInput,
     ) -> *mut TSTree;
 }
+extern "C" {
+    #[doc = " Use the parser to parse some source code, with some additional options."]
+    #[doc = ""]
+    #[doc = " This works the same as `ts_parser_parse`, but the parser also periodically"]
+    #[doc = " invokes the `progress_callback` in the given options, passing it the parser's"]
+    #[doc = " current state along with the options' `payload`. If the callback returns"]
+    #[doc = " `true`, the parser will halt early, returning NULL, just as if a cancellation"]
+    #[doc = " flag had been flipped."]
+    pub fn ts_parser_parse_with_options(
+        self_: *mut TSParser,
+        old_tree: *const TSTree,
+        input: TSInput,
+        parse_options: TSParseOptions,
+    ) -> *mut TSTree;
+}
 extern "C" {
     #[doc = " Use the parser to parse some source code stored in one contiguous buffer."]
     #[doc = " The first two parameters are the same as in the `ts_parser_parse` function"]
@@ -274,6 +308,17 @@ extern "C" {
     #[doc = " call `ts_parser_reset` first."]
     pub fn ts_parser_reset(self_: *mut TSParser);
 }
+extern "C" {
+    #[doc = " Get the byte offset that the parser has reached in the document that it is"]
+    #[doc = " currently parsing."]
+    #[doc = ""]
+    #[doc = " When a parse halts early because of a timeout, a cancellation flag, or a"]
+    #[doc = " progress callback, no partial tree is returned. This function reports how"]
+    #[doc = " far the parser got before halting. It returns zero when no parse is in"]
+    #[doc = " progress, including after a parse has completed successfully and after a"]
+    #[doc = " call to `ts_parser_reset`."]
+    pub fn ts_parser_current_byte_offset(self_: *const TSParser) -> u32;
+}
 extern "C" {
     #[doc = " Set the maximum duration in microseconds that parsing should be allowed to"]
     #[doc = " take before halting."]
@@ -351,6 +396,15 @@ extern "C" {
     #[doc = " The returned pointer must be freed by the caller."]
     pub fn ts_tree_included_ranges(arg1: *const TSTree, length: *mut u32) -> *mut TSRange;
 }
+extern "C" {
+    #[doc = " Get an estimate of the number of bytes of memory used by the syntax tree."]
+    #[doc = ""]
+    #[doc = " Subtrees that are shared with other syntax trees, such as copies of this"]
+    #[doc = " tree or trees that reused its nodes during an incremental parse, are counted"]
+    #[doc = " in full, so the sizes of several related trees may add up to more than the"]
+    #[doc = " memory that they use together."]
+    pub fn ts_tree_size_bytes(self_: *const TSTree) -> usize;
+}
 extern "C" {
     #[doc = " Edit the syntax tree to keep it in sync with source code that has been"]
     #[doc = " edited."]
@@ -390,6 +444,23 @@ extern "C" {
     #[doc = " Get the node's type as a numerical id."]
     pub fn ts_node_symbol(arg1: TSNode) -> TSSymbol;
 }
+extern "C" {
+    #[doc = " Get the parse state in which the parser encountered this node."]
+    #[doc = ""]
+    #[doc = " Nodes that have been edited, and some nodes whose parse state depends on"]
+    #[doc = " their surroundings, do not have a parse state. For these nodes, this"]
+    #[doc = " returns `UINT16_MAX`."]
+    pub fn ts_node_parse_state(arg1: TSNode) -> TSStateId;
+}
+extern "C" {
+    #[doc = " Get the parse state that the parser moved to after this node."]
+    #[doc = ""]
+    #[doc = " Combined with a lookahead iterator, this can be used to find the tokens"]
+    #[doc = " that could have followed this node, for example in order to describe the"]
+    #[doc = " tokens that were expected in place of a syntax error. Returns `UINT16_MAX`"]
+    #[doc = " if the node does not have a parse state."]
+    pub fn ts_node_next_parse_state(arg1: TSNode) -> TSStateId;
+}
 extern "C" {
     #[doc = " Get the node's start byte."]
     pub fn ts_node_start_byte(arg1: TSNode) -> u32;
@@ -788,6 +859,19 @@ extern "C" {
 extern "C" {
     pub fn ts_query_cursor_set_point_range(arg1: *mut TSQueryCursor, arg2: TSPoint, arg3: TSPoint);
 }
+extern "C" {
+    #[doc = " Set the query cursor's current cancellation flag pointer."]
+    #[doc = ""]
+    #[doc = " If a non-null pointer is assigned, then the cursor will periodically read"]
+    #[doc = " from this pointer while searching for matches. If it reads a non-zero value,"]
+    #[doc = " it will halt early, and will not find any further matches until it is"]
+    #[doc = " executed again."]
+    pub fn ts_query_cursor_set_cancellation_flag(self_: *mut TSQueryCursor, flag: *const usize);
+}
+extern "C" {
+    #[doc = " Get the query cursor's current cancellation flag pointer."]
+    pub fn ts_query_cursor_cancellation_flag(self_: *const TSQueryCursor) -> *const usize;
+}
 extern "C" {
     #[doc = " Advance to the next match of the currently running query."]
     #[doc = ""]
@@ -863,6 +947,46 @@ extern "C" {
     #[doc = " See also `ts_parser_set_language`."]
     pub fn ts_language_version(arg1: *const TSLanguage) -> u32;
 }
+extern "C" {
+    #[doc = " Get the number of valid parse states in this language."]
+    pub fn ts_language_state_count(arg1: *const TSLanguage) -> u32;
+}
+extern "C" {
+    #[doc = " Create a new lookahead iterator for the given language and parse state."]
+    #[doc = ""]
+    #[doc = " This returns `NULL` if the state is invalid for the language."]
+    #[doc = ""]
+    #[doc = " Repeatedly call `ts_lookahead_iterator_next` to visit each of the symbols"]
+    #[doc = " that are valid in the given parse state, and call"]
+    #[doc = " `ts_lookahead_iterator_current_symbol` to get the current symbol. The"]
+    #[doc = " symbols include both tokens and the non-terminal symbols that the parser"]
+    #[doc = " can reduce to in that state."]
+    pub fn ts_lookahead_iterator_new(
+        self_: *const TSLanguage,
+        state: TSStateId,
+    ) -> *mut TSLookaheadIterator;
+}
+extern "C" {
+    #[doc = " Delete a lookahead iterator, freeing all of the memory that it used."]
+    pub fn ts_lookahead_iterator_delete(self_: *mut TSLookaheadIterator);
+}
+extern "C" {
+    #[doc = " Advance the lookahead iterator to the next symbol."]
+    #[doc = ""]
+    #[doc = " This returns `true` if there is a new symbol and `false` otherwise."]
+    pub fn ts_lookahead_iterator_next(self_: *mut TSLookaheadIterator) -> bool;
+}
+extern "C" {
+    #[doc = " Get the current symbol of the lookahead iterator."]
+    pub fn ts_lookahead_iterator_current_symbol(self_: *const TSLookaheadIterator) -> TSSymbol;
+}
+extern "C" {
+    #[doc = " Get the current symbol type of the lookahead iterator as a null terminated"]
+    #[doc = " string."]
+    pub fn ts_lookahead_iterator_current_symbol_name(
+        self_: *const TSLookaheadIterator,
+    ) -> *const ::std::os::raw::c_char;
+}
 extern "C" {
     #[doc = " Set the allocation functions used by the library."]
     #[doc = ""]
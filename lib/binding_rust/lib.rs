@@ -5,16 +5,20 @@ mod util;
 use std::os::unix::io::AsRawFd;
 
 use std::{
-    char, error,
+    borrow::Cow,
+    cmp,
+    collections::{HashMap, HashSet},
+    error,
     ffi::CStr,
-    fmt, hash, iter,
+    fmt, hash, io, iter,
     marker::PhantomData,
     mem::MaybeUninit,
     ops,
     os::raw::{c_char, c_void},
     ptr::{self, NonNull},
     slice, str,
-    sync::atomic::AtomicUsize,
+    sync::{atomic::AtomicUsize, Arc, Mutex},
+    time::{Duration, Instant},
     u16,
 };
 
@@ -37,6 +41,10 @@ pub const PARSER_HEADER: &'static str = include_str!("../include/tree_sitter/par
 
 /// An opaque object that defines how to parse a particular language. The code for each
 /// `Language` is generated by the Tree-sitter CLI.
+///
+/// Languages are compared and hashed by the address of their underlying `TSLanguage`,
+/// which is fixed for a given grammar, so a `Language` can be used as the key of a
+/// `HashMap`, for example to cache the compiled queries for each language.
 #[doc(alias = "TSLanguage")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(transparent)]
@@ -82,10 +90,80 @@ pub struct InputEdit {
 #[repr(transparent)]
 pub struct Node<'a>(ffi::TSNode, PhantomData<&'a ()>);
 
+/// A classification of a `Node`, as returned by [Node::kind_class].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Named,
+    Anonymous,
+    Error,
+    Missing,
+    Extra,
+}
+
+/// The position of a `Node` relative to the region affected by an `InputEdit`, as
+/// returned by [Node::overlaps_edit].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EditOverlap {
+    /// The node ends at or before the start of the edit.
+    Before,
+    /// The node starts at or after the end of the edited region.
+    After,
+    /// The edited region lies within the node.
+    Contains,
+    /// The node lies within the edited region.
+    Contained,
+    /// The node partially overlaps the edited region.
+    Straddles,
+}
+
+/// Whether [Node::visit] should walk into a node's children, as returned by
+/// [TreeVisitor::enter].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Descend {
+    /// Visit the node's children.
+    Children,
+    /// Skip the node's children, and continue with its next sibling.
+    Skip,
+}
+
+/// A set of callbacks that are invoked as [Node::visit] walks a syntax tree.
+///
+/// Every node that is passed to [enter](TreeVisitor::enter) is later passed to
+/// [leave](TreeVisitor::leave), after all of its visited descendants have been left.
+pub trait TreeVisitor<'tree> {
+    /// Called when the walk reaches a node, along with the name of the field that
+    /// attaches it to its parent and its depth below the node where the walk started.
+    fn enter(&mut self, node: Node<'tree>, field: Option<&'static str>, depth: usize) -> Descend;
+
+    /// Called when the walk is done with a node and its descendants.
+    fn leave(&mut self, _node: Node<'tree>) {}
+}
+
 /// A stateful object that this is used to produce a `Tree` based on some source code.
 #[doc(alias = "TSParser")]
 pub struct Parser(NonNull<ffi::TSParser>);
 
+/// A thread-safe pool of `Parser`s, keyed by their `Language`.
+///
+/// Reusing parsers avoids the cost of creating a new parser and assigning
+/// its language for every document.
+#[derive(Default)]
+pub struct ParserPool {
+    parsers: Mutex<HashMap<Language, Vec<Parser>>>,
+}
+
+/// A `Parser` that has been taken from a `ParserPool`, and that is returned to
+/// the pool when it is dropped.
+///
+/// When it is returned, the parser is restored to a clean state with
+/// [Parser::reset_full], and its timeout and cancellation flag are cleared. It is
+/// pooled under its language at that time, so its language may be changed while
+/// it is in use. A parser without a language is not returned to the pool.
+pub struct PooledParser<'pool> {
+    pool: &'pool ParserPool,
+    parser: Option<Parser>,
+}
+
 /// A type of log message.
 #[derive(Debug, PartialEq, Eq)]
 pub enum LogType {
@@ -96,15 +174,54 @@ pub enum LogType {
 /// A callback that receives log messages during parser.
 type Logger<'a> = Box<dyn FnMut(LogType, &str) + 'a>;
 
+/// A snapshot of a parser's progress, passed to the callback given to
+/// [Parser::parse_with_progress].
+#[doc(alias = "TSParseState")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseState {
+    current_byte_offset: usize,
+}
+
+/// A summary of the nodes in a syntax `Tree`, as returned by [Tree::stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    /// The total number of nodes, including anonymous nodes.
+    pub node_count: usize,
+    /// The number of named nodes.
+    pub named_node_count: usize,
+    /// The number of `ERROR` and `MISSING` nodes.
+    pub error_count: usize,
+    /// The depth of the most deeply nested node, where the root node has depth 0.
+    pub max_depth: usize,
+    /// The number of bytes spanned by the root node.
+    pub byte_size: usize,
+}
+
+/// A syntax `Tree` together with the source code that it was parsed from.
+///
+/// Because the source is stored alongside the tree, the text of a node can't
+/// accidentally be read from a different buffer, such as an outdated copy of
+/// the document. This is intended for code that doesn't edit the document
+/// incrementally.
+#[derive(Clone, Debug)]
+pub struct ParsedDocument {
+    tree: Tree,
+    source: Vec<u8>,
+}
+
 /// A stateful object for walking a syntax `Tree` efficiently.
 #[doc(alias = "TSTreeCursor")]
 pub struct TreeCursor<'a>(ffi::TSTreeCursor, PhantomData<&'a ()>);
 
 /// A set of patterns that match nodes in a syntax tree.
 #[doc(alias = "TSQuery")]
-#[derive(Debug)]
 pub struct Query {
     ptr: NonNull<ffi::TSQuery>,
+    language: Language,
+    source: Box<[u8]>,
+    disabled_captures: Vec<String>,
+    disabled_patterns: Vec<usize>,
+    predicate_registry: PredicateRegistry,
     capture_names: Vec<String>,
     capture_quantifiers: Vec<Vec<CaptureQuantifier>>,
     text_predicates: Vec<Box<[TextPredicate]>>,
@@ -137,11 +254,35 @@ impl From<ffi::TSQuantifier> for CaptureQuantifier {
 }
 
 /// A stateful object for executing a `Query` on a syntax `Tree`.
+///
+/// A cursor's byte range, point range and match limit are not reset when it is
+/// executed, so they apply to every later call to [QueryCursor::matches] or
+/// [QueryCursor::captures], even with a different query or node. The ranges are
+/// relative to the start of the document, not to the node that the query is
+/// executed on. To remove a range, set it again with an end of zero, for example
+/// `cursor.set_byte_range(0..0)`.
 #[doc(alias = "TSQueryCursor")]
 pub struct QueryCursor {
     ptr: NonNull<ffi::TSQueryCursor>,
 }
 
+/// An iterator over the symbols that are valid in a particular parse state of a
+/// `Language`.
+///
+/// Created with `Language::lookahead_iterator`.
+#[doc(alias = "TSLookaheadIterator")]
+pub struct LookaheadIterator(NonNull<ffi::TSLookaheadIterator>);
+
+/// A builder for configuring a `QueryCursor` before it is used.
+///
+/// Created with `QueryCursor::builder`.
+#[derive(Clone, Debug, Default)]
+pub struct QueryCursorBuilder {
+    byte_range: Option<ops::Range<usize>>,
+    point_range: Option<ops::Range<Point>>,
+    match_limit: Option<u32>,
+}
+
 /// A key-value pair associated with a particular pattern in a `Query`.
 #[derive(Debug, PartialEq, Eq)]
 pub struct QueryProperty {
@@ -163,14 +304,39 @@ pub struct QueryPredicate {
     pub args: Vec<QueryPredicateArg>,
 }
 
+/// A read-only description of a predicate in a `Query`, as returned by
+/// [Query::pattern_predicates].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PredicateInfo {
+    pub operator: Box<str>,
+    pub args: Vec<PredicateInfoArg>,
+}
+
+/// An argument to a predicate described by a `PredicateInfo`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PredicateInfoArg {
+    Capture { index: u32, name: Box<str> },
+    String(Box<str>),
+}
+
 /// A match of a `Query` to a particular set of `Node`s.
 pub struct QueryMatch<'cursor, 'tree> {
     pub pattern_index: usize,
+    /// The match's captures, in the order that the pattern's nodes were matched. See
+    /// [QueryMatch::captures_in_pattern_order].
     pub captures: &'cursor [QueryCapture<'tree>],
     id: u32,
     cursor: *mut ffi::TSQueryCursor,
 }
 
+/// A match of a `Query` that owns its captures, and so is not tied to the
+/// `QueryCursor` that produced it.
+#[derive(Clone, Debug)]
+pub struct OwnedQueryMatch<'tree> {
+    pub pattern_index: usize,
+    pub captures: Vec<QueryCapture<'tree>>,
+}
+
 /// A sequence of `QueryMatch`es associated with a given `QueryCursor`.
 pub struct QueryMatches<'a, 'tree: 'a, T: TextProvider<'a>> {
     ptr: *mut ffi::TSQueryCursor,
@@ -181,6 +347,16 @@ pub struct QueryMatches<'a, 'tree: 'a, T: TextProvider<'a>> {
     _tree: PhantomData<&'tree ()>,
 }
 
+/// A sequence of `QueryMatch`es that stops once a deadline has passed.
+///
+/// Created with `QueryCursor::matches_with_deadline`.
+pub struct DeadlineQueryMatches<'a, 'tree: 'a, T: TextProvider<'a>> {
+    matches: QueryMatches<'a, 'tree, T>,
+    deadline: Instant,
+    matches_until_check: usize,
+    deadline_exceeded: bool,
+}
+
 /// A sequence of `QueryCapture`s associated with a given `QueryCursor`.
 pub struct QueryCaptures<'a, 'tree: 'a, T: TextProvider<'a>> {
     ptr: *mut ffi::TSQueryCursor,
@@ -191,8 +367,52 @@ pub struct QueryCaptures<'a, 'tree: 'a, T: TextProvider<'a>> {
     _tree: PhantomData<&'tree ()>,
 }
 
+/// A sequence of `QueryCapture`s along with the text of each captured node.
+///
+/// Created with `QueryCursor::captures_with_text`.
+pub struct QueryCapturesWithText<'a, 'tree: 'a, T: TextProvider<'a>> {
+    captures: QueryCaptures<'a, 'tree, TextCache<'a, T>>,
+}
+
+/// A `TextProvider` that remembers the text of the nodes that it has been asked for,
+/// along with their ids and start bytes, so that `QueryCapturesWithText` can reuse the
+/// text that was resolved while evaluating predicates.
+struct TextCache<'a, T: TextProvider<'a>> {
+    text_provider: T,
+    texts: Vec<(usize, usize, Cow<'a, [u8]>)>,
+}
+
+/// A sequence of `QueryCapture`s that yields at most one capture per node range.
+///
+/// Created with `QueryCursor::captures_deduped`.
+pub struct DedupedQueryCaptures<'a, 'tree: 'a, T: TextProvider<'a>> {
+    captures: QueryCaptures<'a, 'tree, T>,
+    seen_ranges: Vec<ops::Range<usize>>,
+}
+
+/// A sequence of `QueryCapture`s that assigns each byte of the document to at most
+/// one capture, based on pattern order.
+///
+/// Created with `QueryCursor::captures_by_priority`.
+pub struct PriorityQueryCaptures<'a, 'tree: 'a, T: TextProvider<'a>> {
+    captures: QueryCaptures<'a, 'tree, T>,
+    pending: Option<(usize, QueryCapture<'tree>)>,
+    active: Vec<(usize, QueryCapture<'tree>)>,
+    position: usize,
+}
+
+/// A source for the text of a syntax tree's nodes, which is used to evaluate the
+/// text predicates in a [Query].
+///
+/// The text of a node is returned as a sequence of chunks, so the source code does not
+/// need to be stored in one contiguous slice. The chunks can either borrow from the
+/// source code, or be owned, for sources such as ropes that can only materialize a
+/// node's text on demand. This trait is implemented for `&[u8]`, and for closures that
+/// take a [Node] and return an iterator of chunks that convert into a `Cow<[u8]>`,
+/// such as `&[u8]` slices of the source code or `Vec<u8>` buffers that were copied out
+/// of a rope.
 pub trait TextProvider<'a> {
-    type I: Iterator<Item = &'a [u8]> + 'a;
+    type I: Iterator<Item = Cow<'a, [u8]>> + 'a;
     fn text(&mut self, node: Node) -> Self::I;
 }
 
@@ -217,13 +437,36 @@ pub struct IncludedRangesError(pub usize);
 /// An error that occurred when trying to create a `Query`.
 #[derive(Debug, PartialEq, Eq)]
 pub struct QueryError {
+    /// The zero-based row of the error's position in the query source.
     pub row: usize,
+    /// The zero-based column of the error's position, in bytes.
     pub column: usize,
+    /// The byte offset of the error's position in the query source.
+    ///
+    /// For [QueryErrorKind::NodeType], [QueryErrorKind::Field] and
+    /// [QueryErrorKind::Capture] errors, this is the start of the unrecognized name.
+    /// For [QueryErrorKind::Language] errors, the position is always zero. For
+    /// [QueryErrorKind::Predicate] errors, it is the position of the regex for an invalid
+    /// regex, and zero for all other predicate errors, which have no position.
     pub offset: usize,
+    /// For errors about unrecognized names, the name itself. For other errors, the
+    /// line of the query containing the error, followed by a caret pointing at it.
     pub message: String,
     pub kind: QueryErrorKind,
 }
 
+/// Any error produced by this crate.
+///
+/// Each fallible API returns its own specific error type. This enum allows
+/// code that calls several of them to propagate all of those errors with `?`.
+#[derive(Debug)]
+pub enum Error {
+    Language(LanguageError),
+    IncludedRanges(IncludedRangesError),
+    Query(QueryError),
+    Io(io::Error),
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum QueryErrorKind {
     Syntax,
@@ -239,7 +482,30 @@ pub enum QueryErrorKind {
 enum TextPredicate {
     CaptureEqString(u32, String, bool),
     CaptureEqCapture(u32, u32, bool),
+    CaptureEqStringCi(u32, String, bool),
+    CaptureEqCaptureCi(u32, u32, bool),
     CaptureMatchString(u32, regex::bytes::Regex, bool),
+    Custom(QueryPredicate, Arc<RegisteredPredicate>),
+}
+
+/// A set of custom predicate functions that can be used in a `Query`.
+///
+/// Each predicate is registered with a function that validates its arguments when
+/// a query is created, and a function that evaluates it against each match. See
+/// [Query::new_with_predicates].
+#[derive(Clone, Default)]
+pub struct PredicateRegistry {
+    predicates: HashMap<String, Arc<RegisteredPredicate>>,
+}
+
+type PredicateValidateFn = dyn Fn(&QueryPredicate) -> Result<(), String> + Send + Sync;
+
+type PredicateEvaluateFn =
+    dyn Fn(&QueryPredicate, &QueryMatch, &mut dyn FnMut(Node) -> Vec<u8>) -> bool + Send + Sync;
+
+struct RegisteredPredicate {
+    validate: Box<PredicateValidateFn>,
+    evaluate: Box<PredicateEvaluateFn>,
 }
 
 // TODO: Remove this struct at at some point. If `core::str::lossy::Utf8Lossy`
@@ -257,6 +523,36 @@ impl Language {
         unsafe { ffi::ts_language_version(self.0) as usize }
     }
 
+    /// Get the ABI version number of this `Language`.
+    ///
+    /// This is the same as [Language::version], and can be compared against
+    /// [LANGUAGE_VERSION] and [MIN_COMPATIBLE_LANGUAGE_VERSION] to check whether
+    /// a grammar is compatible with this library.
+    #[doc(alias = "ts_language_version")]
+    pub fn abi_version(&self) -> usize {
+        self.version()
+    }
+
+    /// Get the number of valid parse states in this language.
+    #[doc(alias = "ts_language_state_count")]
+    pub fn parse_state_count(&self) -> usize {
+        unsafe { ffi::ts_language_state_count(self.0) as usize }
+    }
+
+    /// Create an iterator over the symbols that are valid in the given parse state.
+    ///
+    /// The symbols include the tokens that the parser could see next in that state, as
+    /// well as the non-terminal symbols that it could reduce to. Combined with
+    /// [Node::next_parse_state], this can be used to describe the tokens that were
+    /// expected where a syntax error begins.
+    ///
+    /// Returns `None` if the state is not valid for this language.
+    #[doc(alias = "ts_lookahead_iterator_new")]
+    pub fn lookahead_iterator(&self, state: u16) -> Option<LookaheadIterator> {
+        let ptr = unsafe { ffi::ts_lookahead_iterator_new(self.0, state) };
+        NonNull::new(ptr).map(LookaheadIterator)
+    }
+
     /// Get the number of distinct node types in this language.
     #[doc(alias = "ts_language_symbol_count")]
     pub fn node_kind_count(&self) -> usize {
@@ -334,6 +630,27 @@ impl Language {
             Some(id)
         }
     }
+
+    /// Get the numerical ids for several field names at once.
+    ///
+    /// The result has one entry per name, in the same order, so that code which
+    /// extracts fields from this language can build its table of ids up front.
+    pub fn field_ids_for_names(&self, names: &[&str]) -> Vec<Option<u16>> {
+        names
+            .iter()
+            .map(|name| self.field_id_for_name(name))
+            .collect()
+    }
+
+    /// Get the numeric ids for several node kinds at once.
+    ///
+    /// As with [Language::id_for_node_kind], unknown kinds map to zero.
+    pub fn ids_for_node_kinds(&self, kinds: &[&str], named: bool) -> Vec<u16> {
+        kinds
+            .iter()
+            .map(|kind| self.id_for_node_kind(kind, named))
+            .collect()
+    }
 }
 
 impl Parser {
@@ -458,6 +775,16 @@ impl Parser {
     ///  * The parser has not yet had a language assigned with [Parser::set_language]
     ///  * The timeout set with [Parser::set_timeout_micros] expired
     ///  * The cancellation flag set with [Parser::set_cancellation_flag] was flipped
+    ///
+    /// A parse that halts because of a timeout or cancellation does not produce a
+    /// partial tree. Use [Parser::current_byte_offset] to find out how far it got.
+    ///
+    /// When a document is reparsed from scratch, there is no benefit to passing
+    /// the previous tree as `old_tree`: only the unchanged parts of an edited tree
+    /// are reused, and a tree that was not edited to match the new text will
+    /// produce incorrect results. To limit allocations when parsing many
+    /// documents, reuse the same `Parser` instead, since it retains its internal
+    /// buffers between parses.
     #[doc(alias = "ts_parser_parse")]
     pub fn parse(&mut self, text: impl AsRef<[u8]>, old_tree: Option<&Tree>) -> Option<Tree> {
         let bytes = text.as_ref();
@@ -468,8 +795,88 @@ impl Parser {
         )
     }
 
+    /// Apply an edit to a previous syntax tree and then incrementally parse the
+    /// edited document.
+    ///
+    /// This is equivalent to calling [Tree::edit] on `old_tree` followed by
+    /// [Parser::parse] with `new_text`. Afterward, `old_tree` remains edited, so it can
+    /// be compared with the returned tree using [Tree::changed_ranges].
+    ///
+    /// Returns `None` under the same conditions as [Parser::parse].
+    pub fn reparse(
+        &mut self,
+        old_tree: &mut Tree,
+        edit: &InputEdit,
+        new_text: impl AsRef<[u8]>,
+    ) -> Option<Tree> {
+        old_tree.edit(edit);
+        self.parse(new_text, Some(old_tree))
+    }
+
+    /// Parse a slice of UTF8 text, and also return the ranges of all of the `ERROR`
+    /// and `MISSING` nodes in the resulting tree, in document order.
+    ///
+    /// The tree is only walked if its root node [has errors](Node::has_error), and
+    /// error-free subtrees are skipped, as in [Tree::errors].
+    ///
+    /// Returns `None` under the same conditions as [Parser::parse].
+    pub fn parse_with_errors(
+        &mut self,
+        text: impl AsRef<[u8]>,
+        old_tree: Option<&Tree>,
+    ) -> Option<(Tree, Vec<Range>)> {
+        let tree = self.parse(text, old_tree)?;
+        let errors = if tree.root_node().has_error() {
+            tree.errors().iter().map(Node::range).collect()
+        } else {
+            Vec::new()
+        };
+        Some((tree, errors))
+    }
+
+    /// Parse the regions of a document that belong to an injected language.
+    ///
+    /// # Arguments:
+    /// * `full_source` The UTF8-encoded text of the entire document.
+    /// * `ranges` The ranges of the document that contain the injected language.
+    ///   These must satisfy the same requirements as in [Parser::set_included_ranges].
+    /// * `old_tree` A previous syntax tree parsed from the same ranges of the same
+    ///   document, edited to match any changes to the text using [Tree::edit].
+    ///
+    /// Because the whole document is passed to the parser, the positions of the
+    /// nodes in the resulting tree are relative to the start of the document,
+    /// rather than to the start of the first range. The parser's included ranges
+    /// are restored to their previous value before this method returns.
+    pub fn parse_injection(
+        &mut self,
+        full_source: &[u8],
+        ranges: &[Range],
+        old_tree: Option<&Tree>,
+    ) -> Result<Option<Tree>, IncludedRangesError> {
+        let prev_ranges = unsafe {
+            let mut count = 0u32;
+            let ptr = ffi::ts_parser_included_ranges(self.0.as_ptr(), &mut count as *mut u32);
+            slice::from_raw_parts(ptr, count as usize).to_vec()
+        };
+
+        self.set_included_ranges(ranges)?;
+        let tree = self.parse(full_source, old_tree);
+        unsafe {
+            ffi::ts_parser_set_included_ranges(
+                self.0.as_ptr(),
+                prev_ranges.as_ptr(),
+                prev_ranges.len() as u32,
+            )
+        };
+        Ok(tree)
+    }
+
     /// Parse a slice of UTF16 text.
     ///
+    /// The code units are interpreted in the platform's native byte order. To
+    /// parse raw bytes with a fixed byte order, as when reading a file, use
+    /// [Parser::parse_utf16_le] or [Parser::parse_utf16_be].
+    ///
     /// # Arguments:
     /// * `text` The UTF16-encoded text to parse.
     /// * `old_tree` A previous syntax tree parsed from the same document.
@@ -489,8 +896,49 @@ impl Parser {
         )
     }
 
+    /// Parse UTF16 text stored as little-endian bytes.
+    ///
+    /// The bytes are converted to native code units before parsing, and a trailing
+    /// odd byte is ignored. A byte order mark, if present, is not stripped. As with
+    /// [Parser::parse_utf16], byte offsets in the resulting tree are measured in
+    /// the UTF16 text, and so correspond to offsets in `input`.
+    pub fn parse_utf16_le(
+        &mut self,
+        input: impl AsRef<[u8]>,
+        old_tree: Option<&Tree>,
+    ) -> Option<Tree> {
+        let code_points = input
+            .as_ref()
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        self.parse_utf16(code_points, old_tree)
+    }
+
+    /// Parse UTF16 text stored as big-endian bytes.
+    ///
+    /// See [Parser::parse_utf16_le] for details.
+    pub fn parse_utf16_be(
+        &mut self,
+        input: impl AsRef<[u8]>,
+        old_tree: Option<&Tree>,
+    ) -> Option<Tree> {
+        let code_points = input
+            .as_ref()
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        self.parse_utf16(code_points, old_tree)
+    }
+
     /// Parse UTF8 text provided in chunks by a callback.
     ///
+    /// The callback can return any type that can be viewed as bytes, such as
+    /// `&str`, `String`, `&[u8]` or `Vec<u8>`. Owned values are kept alive until
+    /// the next time the callback is called. If the callback returns byte slices
+    /// or byte string literals of differing lengths, use
+    /// [Parser::parse_with_slices] to avoid annotating its return type.
+    ///
     /// # Arguments:
     /// * `callback` A function that takes a byte offset and position and
     ///   returns a slice of UTF8-encoded text starting at that byte offset
@@ -539,6 +987,85 @@ impl Parser {
         }
     }
 
+    /// Parse UTF8 text provided in chunks by a callback that returns byte slices.
+    ///
+    /// This is the same as [Parser::parse_with], but because the callback's
+    /// return type is fixed, it can return byte string literals or slices of
+    /// different lengths, like `b"["` and `&[]`, without a type annotation.
+    pub fn parse_with_slices<'a, F: FnMut(usize, Point) -> &'a [u8]>(
+        &mut self,
+        callback: &mut F,
+        old_tree: Option<&Tree>,
+    ) -> Option<Tree> {
+        self.parse_with(callback, old_tree)
+    }
+
+    /// Parse a slice of UTF8 text, periodically reporting progress to a callback.
+    ///
+    /// # Arguments:
+    /// * `text` The UTF8-encoded text to parse.
+    /// * `old_tree` A previous syntax tree parsed from the same document.
+    ///   If the text of the document has changed since `old_tree` was
+    ///   created, then you must edit `old_tree` to match the new text using
+    ///   [Tree::edit].
+    /// * `on_progress` A function that is called periodically during parsing
+    ///   with the parser's current [ParseState]. If it returns `false`, parsing
+    ///   halts early and this method returns `None`.
+    ///
+    /// Halting via the callback behaves like the cancellation flag set with
+    /// [Parser::set_cancellation_flag]: the next call to `parse` will resume
+    /// where this one left off, unless you call [Parser::reset] first.
+    #[doc(alias = "ts_parser_parse_with_options")]
+    pub fn parse_with_progress<F: FnMut(ParseState) -> bool>(
+        &mut self,
+        text: impl AsRef<[u8]>,
+        old_tree: Option<&Tree>,
+        mut on_progress: F,
+    ) -> Option<Tree> {
+        let mut bytes = text.as_ref();
+
+        // This C function is passed to Tree-sitter as the input callback.
+        unsafe extern "C" fn read(
+            payload: *mut c_void,
+            byte_offset: u32,
+            _: ffi::TSPoint,
+            bytes_read: *mut u32,
+        ) -> *const c_char {
+            let bytes = *(payload as *const &[u8]);
+            let slice = bytes.get(byte_offset as usize..).unwrap_or(&[]);
+            *bytes_read = slice.len() as u32;
+            slice.as_ptr() as *const c_char
+        }
+
+        // This C function is passed to Tree-sitter as the progress callback.
+        unsafe extern "C" fn progress<F: FnMut(ParseState) -> bool>(
+            state: *mut ffi::TSParseState,
+        ) -> bool {
+            let state = &*state;
+            let callback = (state.payload as *mut F).as_mut().unwrap();
+            !callback(ParseState {
+                current_byte_offset: state.current_byte_offset as usize,
+            })
+        }
+
+        let c_input = ffi::TSInput {
+            payload: &mut bytes as *mut &[u8] as *mut c_void,
+            read: Some(read),
+            encoding: ffi::TSInputEncoding_TSInputEncodingUTF8,
+        };
+        let c_options = ffi::TSParseOptions {
+            payload: &mut on_progress as *mut F as *mut c_void,
+            progress_callback: Some(progress::<F>),
+        };
+
+        let c_old_tree = old_tree.map_or(ptr::null_mut(), |t| t.0.as_ptr());
+        unsafe {
+            let c_new_tree =
+                ffi::ts_parser_parse_with_options(self.0.as_ptr(), c_old_tree, c_input, c_options);
+            NonNull::new(c_new_tree).map(Tree)
+        }
+    }
+
     /// Parse UTF16 text provided in chunks by a callback.
     ///
     /// # Arguments:
@@ -606,6 +1133,31 @@ impl Parser {
         unsafe { ffi::ts_parser_reset(self.0.as_ptr()) }
     }
 
+    /// Get the byte offset that the parser has reached in the document that it is
+    /// currently parsing.
+    ///
+    /// When a parse halts early because of a timeout, a cancellation flag or a
+    /// progress callback, the parsing method returns `None` rather than a partial
+    /// tree. This reports how far the parser got before halting, which is where it
+    /// will resume on the next call. It returns zero when no parse is in progress,
+    /// including after a parse has completed and after a call to [Parser::reset].
+    #[doc(alias = "ts_parser_current_byte_offset")]
+    pub fn current_byte_offset(&self) -> usize {
+        unsafe { ffi::ts_parser_current_byte_offset(self.0.as_ptr()) as usize }
+    }
+
+    /// Restore the parser to a clean state, so that it can be reused for an
+    /// unrelated document.
+    ///
+    /// In addition to everything that [Parser::reset] does, this also clears the
+    /// parser's included ranges, so that the whole document is parsed, and removes
+    /// the parser's logger. The parser's language and timeout are preserved.
+    pub fn reset_full(&mut self) {
+        self.reset();
+        self.set_included_ranges(&[]).unwrap();
+        self.set_logger(None);
+    }
+
     /// Get the duration in microseconds that parsing is allowed to take.
     ///
     /// This is set via [set_timeout_micros](Parser::set_timeout_micros).
@@ -624,6 +1176,27 @@ impl Parser {
         unsafe { ffi::ts_parser_set_timeout_micros(self.0.as_ptr(), timeout_micros) }
     }
 
+    /// Get the duration that parsing is allowed to take.
+    ///
+    /// This is set via [set_timeout](Parser::set_timeout). A zero duration means
+    /// that there is no timeout.
+    #[doc(alias = "ts_parser_timeout_micros")]
+    pub fn timeout(&self) -> Duration {
+        Duration::from_micros(self.timeout_micros())
+    }
+
+    /// Set the maximum duration that parsing should be allowed to take before
+    /// halting.
+    ///
+    /// This is equivalent to [set_timeout_micros](Parser::set_timeout_micros),
+    /// truncating the duration to whole microseconds. A zero duration disables
+    /// the timeout.
+    #[doc(alias = "ts_parser_set_timeout_micros")]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        let timeout_micros = u64::try_from(timeout.as_micros()).unwrap_or(u64::MAX);
+        self.set_timeout_micros(timeout_micros)
+    }
+
     /// Set the ranges of text that the parser should include when parsing.
     ///
     /// By default, the parser will always include entire documents. This function
@@ -632,19 +1205,30 @@ impl Parser {
     /// multiple disjoint ranges.
     ///
     /// If `ranges` is empty, then the entire document will be parsed. Otherwise,
-    /// the given ranges must be ordered from earliest to latest in the document,
-    /// and they must not overlap. That is, the following must hold for all
-    /// `i` < `length - 1`:
+    /// the given ranges must be non-empty, ordered from earliest to latest in the
+    /// document, and they must not overlap. That is, the following must hold for
+    /// all `i` < `length - 1`:
     /// ```text
-    ///     ranges[i].end_byte <= ranges[i + 1].start_byte
+    ///     ranges[i].start_byte < ranges[i].end_byte <= ranges[i + 1].start_byte
     /// ```
     /// If this requirement is not satisfied, method will return IncludedRangesError
-    /// error with an offset in the passed ranges slice pointing to a first incorrect range.
+    /// error with an offset in the passed ranges slice pointing to a first incorrect range,
+    /// and the parser's included ranges are left unchanged.
     #[doc(alias = "ts_parser_set_included_ranges")]
     pub fn set_included_ranges<'a>(
         &mut self,
         ranges: &'a [Range],
     ) -> Result<(), IncludedRangesError> {
+        // The C library accepts zero-length ranges, so validate the ranges here
+        // rather than relying on its result.
+        let mut prev_end_byte = 0;
+        for (i, range) in ranges.iter().enumerate() {
+            if range.start_byte < prev_end_byte || range.end_byte <= range.start_byte {
+                return Err(IncludedRangesError(i));
+            }
+            prev_end_byte = range.end_byte;
+        }
+
         let ts_ranges: Vec<ffi::TSRange> =
             ranges.iter().cloned().map(|range| range.into()).collect();
         let result = unsafe {
@@ -654,17 +1238,9 @@ impl Parser {
                 ts_ranges.len() as u32,
             )
         };
-
         if result {
             Ok(())
         } else {
-            let mut prev_end_byte = 0;
-            for (i, range) in ranges.iter().enumerate() {
-                if range.start_byte < prev_end_byte || range.end_byte < range.start_byte {
-                    return Err(IncludedRangesError(i));
-                }
-                prev_end_byte = range.end_byte;
-            }
             Err(IncludedRangesError(0))
         }
     }
@@ -701,6 +1277,78 @@ impl Drop for Parser {
     }
 }
 
+impl ParserPool {
+    /// Create a new, empty parser pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a parser for the given language out of the pool, creating a new one
+    /// if none is available.
+    ///
+    /// Returns an error if a new parser is needed and the language is incompatible
+    /// with this version of Tree-sitter. See [Parser::set_language].
+    pub fn get(&self, language: Language) -> Result<PooledParser<'_>, LanguageError> {
+        let pooled = self
+            .parsers
+            .lock()
+            .unwrap()
+            .get_mut(&language)
+            .and_then(Vec::pop);
+        let parser = match pooled {
+            Some(parser) => parser,
+            None => {
+                let mut parser = Parser::new();
+                parser.set_language(language)?;
+                parser
+            }
+        };
+        Ok(PooledParser {
+            pool: self,
+            parser: Some(parser),
+        })
+    }
+}
+
+impl<'pool> ops::Deref for PooledParser<'pool> {
+    type Target = Parser;
+
+    fn deref(&self) -> &Parser {
+        self.parser.as_ref().unwrap()
+    }
+}
+
+impl<'pool> ops::DerefMut for PooledParser<'pool> {
+    fn deref_mut(&mut self) -> &mut Parser {
+        self.parser.as_mut().unwrap()
+    }
+}
+
+impl<'pool> Drop for PooledParser<'pool> {
+    fn drop(&mut self) {
+        let mut parser = self.parser.take().unwrap();
+        if let Some(language) = parser.language() {
+            parser.reset_full();
+            parser.set_timeout_micros(0);
+            unsafe { parser.set_cancellation_flag(None) };
+            self.pool
+                .parsers
+                .lock()
+                .unwrap()
+                .entry(language)
+                .or_default()
+                .push(parser);
+        }
+    }
+}
+
+impl ParseState {
+    /// Get the byte offset in the document that the parser has reached.
+    pub fn current_byte_offset(&self) -> usize {
+        self.current_byte_offset
+    }
+}
+
 impl Tree {
     /// Get the root node of the syntax tree.
     #[doc(alias = "ts_tree_root_node")]
@@ -722,6 +1370,16 @@ impl Tree {
         .unwrap()
     }
 
+    /// Follow a sequence of child indices down from the root node.
+    ///
+    /// This resolves a path produced by [Node::path_from_root], possibly one that was
+    /// computed on an earlier version of the tree. Returns `None` if any index is out
+    /// of bounds. An empty path returns the root node.
+    pub fn node_at_path(&self, path: &[usize]) -> Option<Node<'_>> {
+        path.iter()
+            .try_fold(self.root_node(), |node, &index| node.child(index))
+    }
+
     /// Get the language that was used to parse the syntax tree.
     #[doc(alias = "ts_tree_language")]
     pub fn language(&self) -> Language {
@@ -739,6 +1397,20 @@ impl Tree {
         unsafe { ffi::ts_tree_edit(self.0.as_ptr(), &edit) };
     }
 
+    /// Apply several edits to the syntax tree, in order.
+    ///
+    /// Each edit is applied to the tree as already modified by the previous edits,
+    /// so its byte offsets and positions must be expressed in terms of the document
+    /// *after* all of the preceding edits in the slice. Adjusting the offsets of the
+    /// later edits is the caller's responsibility. [InputEdit::from_changes] computes
+    /// a correctly adjusted sequence of edits from a set of replacements in the
+    /// original document.
+    pub fn edit_all(&mut self, edits: &[InputEdit]) {
+        for edit in edits {
+            self.edit(edit);
+        }
+    }
+
     /// Create a new [TreeCursor] starting from the root of the tree.
     pub fn walk(&self) -> TreeCursor {
         self.root_node().walk()
@@ -751,6 +1423,9 @@ impl Tree {
     /// ranges match up to the new tree. Generally, you'll want to call this method right
     /// after calling one of the [Parser::parse] functions. Call it on the old tree that
     /// was passed to parse, and pass the new tree that was returned from `parse`.
+    ///
+    /// The ranges are computed up front, so the iterator's [ExactSizeIterator::len] is
+    /// always the number of ranges that remain. It is zero when the trees are identical.
     #[doc(alias = "ts_tree_get_changed_ranges")]
     pub fn changed_ranges(&self, other: &Tree) -> impl ExactSizeIterator<Item = Range> {
         let mut count = 0u32;
@@ -764,23 +1439,231 @@ impl Tree {
         }
     }
 
-    /// Get the included ranges that were used to parse the syntax tree.
-    pub fn included_ranges(&self) -> Vec<Range> {
-        let mut count = 0u32;
-        unsafe {
-            let ptr = ffi::ts_tree_included_ranges(self.0.as_ptr(), &mut count as *mut u32);
-            let ranges = slice::from_raw_parts(ptr, count as usize);
-            let result = ranges.iter().copied().map(|range| range.into()).collect();
-            (FREE_FN)(ptr as *mut c_void);
-            result
+    /// Check if this syntax tree has the same structure as another syntax tree.
+    ///
+    /// The two trees are walked in lockstep, and every pair of corresponding nodes
+    /// must have the same kind, the same field name, and the same byte range. This
+    /// is useful for checking that an incremental reparse produced the same tree as
+    /// a parse from scratch.
+    pub fn structurally_eq(&self, other: &Tree) -> bool {
+        let mut cursor1 = self.walk();
+        let mut cursor2 = other.walk();
+        loop {
+            let node1 = cursor1.node();
+            let node2 = cursor2.node();
+            if node1.kind_id() != node2.kind_id()
+                || cursor1.field_id() != cursor2.field_id()
+                || node1.byte_range() != node2.byte_range()
+            {
+                return false;
+            }
+
+            let has_child = cursor1.goto_first_child();
+            if has_child != cursor2.goto_first_child() {
+                return false;
+            }
+            if has_child {
+                continue;
+            }
+
+            loop {
+                let has_sibling = cursor1.goto_next_sibling();
+                if has_sibling != cursor2.goto_next_sibling() {
+                    return false;
+                }
+                if has_sibling {
+                    break;
+                }
+                if !cursor1.goto_parent() {
+                    return true;
+                }
+                cursor2.goto_parent();
+            }
         }
     }
 
-    /// Print a graph of the tree to the given file descriptor.
-    /// The graph is formatted in the DOT language. You may want to pipe this graph
-    /// directly to a `dot(1)` process in order to generate SVG output.
-    #[cfg(unix)]
-    #[doc(alias = "ts_tree_print_dot_graph")]
+    /// Collect all of the `ERROR` and `MISSING` nodes in this syntax tree, in
+    /// document order.
+    ///
+    /// Subtrees that do not contain any errors are skipped. Use [Node::range] on
+    /// the returned nodes to report their locations.
+    pub fn errors(&self) -> Vec<Node<'_>> {
+        let mut result = Vec::new();
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_error() || node.is_missing() {
+                result.push(node);
+            }
+
+            if node.has_error() && cursor.goto_first_child() {
+                continue;
+            }
+
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Compute a summary of the nodes in this syntax tree, in a single traversal.
+    pub fn stats(&self) -> TreeStats {
+        let root = self.root_node();
+        let mut stats = TreeStats {
+            byte_size: root.end_byte() - root.start_byte(),
+            ..TreeStats::default()
+        };
+        let mut cursor = self.walk();
+        let mut depth = 0;
+        loop {
+            let node = cursor.node();
+            stats.node_count += 1;
+            if node.is_named() {
+                stats.named_node_count += 1;
+            }
+            if node.is_error() || node.is_missing() {
+                stats.error_count += 1;
+            }
+            stats.max_depth = stats.max_depth.max(depth);
+
+            if cursor.goto_first_child() {
+                depth += 1;
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return stats;
+                }
+                depth -= 1;
+            }
+        }
+    }
+
+    /// Collect the numerical ids of all of the node kinds that occur in this
+    /// syntax tree, including anonymous nodes.
+    ///
+    /// Use [Language::node_kind_for_id] to get the names of the kinds.
+    pub fn kinds_present(&self) -> HashSet<u16> {
+        let mut result = HashSet::new();
+        let mut cursor = self.walk();
+        loop {
+            result.insert(cursor.node().kind_id());
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Convert this syntax tree into a nested JSON value.
+    ///
+    /// Each node becomes an object with its `kind`, whether it is `named`, the
+    /// `field` name under which it appears in its parent (or `null`), its byte
+    /// and point range, and an array of its `children`, including anonymous
+    /// nodes.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{json, Map, Value};
+
+        fn node_object(node: Node, field_name: Option<&str>) -> Map<String, Value> {
+            let point = |point: Point| json!({ "row": point.row, "column": point.column });
+            let mut object = Map::new();
+            object.insert("kind".into(), node.kind().into());
+            object.insert("named".into(), node.is_named().into());
+            object.insert("field".into(), field_name.into());
+            object.insert("start_byte".into(), node.start_byte().into());
+            object.insert("end_byte".into(), node.end_byte().into());
+            object.insert("start_point".into(), point(node.start_position()));
+            object.insert("end_point".into(), point(node.end_position()));
+            object
+        }
+
+        let mut cursor = self.walk();
+        let mut stack = vec![(node_object(cursor.node(), None), Vec::new())];
+        loop {
+            if cursor.goto_first_child() {
+                stack.push((node_object(cursor.node(), cursor.field_name()), Vec::new()));
+                continue;
+            }
+            loop {
+                let (mut object, children) = stack.pop().unwrap();
+                object.insert("children".into(), Value::Array(children));
+                match stack.last_mut() {
+                    Some((_, siblings)) => siblings.push(Value::Object(object)),
+                    None => return Value::Object(object),
+                }
+                if cursor.goto_next_sibling() {
+                    stack.push((node_object(cursor.node(), cursor.field_name()), Vec::new()));
+                    break;
+                }
+                cursor.goto_parent();
+            }
+        }
+    }
+
+    /// Collect the [fold ranges](Node::fold_range) of all of the nodes in this
+    /// syntax tree whose kinds have the given numerical ids.
+    ///
+    /// Nodes that start and end on the same row are skipped, since there is
+    /// nothing to fold. The ranges are ordered by their nodes' positions in the
+    /// tree, so outer nodes come before the nodes nested within them.
+    pub fn fold_ranges(&self, kinds: &[u16]) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_any_kind(kinds) {
+                let (start_row, end_row) = node.fold_range();
+                if start_row < end_row {
+                    result.push((start_row, end_row));
+                }
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Get the included ranges that were used to parse the syntax tree.
+    pub fn included_ranges(&self) -> Vec<Range> {
+        let mut count = 0u32;
+        unsafe {
+            let ptr = ffi::ts_tree_included_ranges(self.0.as_ptr(), &mut count as *mut u32);
+            let ranges = slice::from_raw_parts(ptr, count as usize);
+            let result = ranges.iter().copied().map(|range| range.into()).collect();
+            (FREE_FN)(ptr as *mut c_void);
+            result
+        }
+    }
+
+    /// Get an estimate of the number of bytes of memory used by the syntax tree.
+    ///
+    /// This counts the tree's nodes, including anonymous and hidden ones, but not the
+    /// allocator's own overhead or the [Language]. Nodes are shared between a tree and
+    /// its clones, and between an old tree and a new tree that reused its nodes during
+    /// an incremental parse. Shared nodes are counted in full by every tree, so the sum
+    /// of the sizes of related trees overestimates the memory that they use together.
+    #[doc(alias = "ts_tree_size_bytes")]
+    pub fn size_bytes(&self) -> usize {
+        unsafe { ffi::ts_tree_size_bytes(self.0.as_ptr()) }
+    }
+
+    /// Print a graph of the tree to the given file descriptor.
+    /// The graph is formatted in the DOT language. You may want to pipe this graph
+    /// directly to a `dot(1)` process in order to generate SVG output.
+    #[cfg(unix)]
+    #[doc(alias = "ts_tree_print_dot_graph")]
     pub fn print_dot_graph(&self, file: &impl AsRawFd) {
         let fd = file.as_raw_fd();
         unsafe { ffi::ts_tree_print_dot_graph(self.0.as_ptr(), fd) }
@@ -805,6 +1688,50 @@ impl Clone for Tree {
     }
 }
 
+impl ParsedDocument {
+    /// Parse the given source code and keep it together with the resulting tree.
+    ///
+    /// Returns `None` under the same conditions as [Parser::parse].
+    pub fn parse(parser: &mut Parser, source: impl Into<Vec<u8>>) -> Option<Self> {
+        let source = source.into();
+        let tree = parser.parse(&source, None)?;
+        Some(ParsedDocument { tree, source })
+    }
+
+    /// Get the syntax tree.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Get the source code that the tree was parsed from.
+    pub fn source(&self) -> &[u8] {
+        &self.source
+    }
+
+    /// Get the root node of the syntax tree.
+    pub fn root_node(&self) -> Node<'_> {
+        self.tree.root_node()
+    }
+
+    /// Get the source code of the given node, which must belong to this document.
+    /// This is checked in debug builds.
+    pub fn node_text(&self, node: Node) -> &[u8] {
+        debug_assert!(node.same_tree(&self.root_node()));
+        &self.source[node.byte_range()]
+    }
+
+    /// Get the source code of the given node, which must belong to this document,
+    /// as a string.
+    pub fn node_utf8_text(&self, node: Node) -> Result<&str, str::Utf8Error> {
+        str::from_utf8(self.node_text(node))
+    }
+
+    /// Split the document into its syntax tree and source code.
+    pub fn into_parts(self) -> (Tree, Vec<u8>) {
+        (self.tree, self.source)
+    }
+}
+
 impl<'tree> Node<'tree> {
     fn new(node: ffi::TSNode) -> Option<Self> {
         if node.id.is_null() {
@@ -820,6 +1747,11 @@ impl<'tree> Node<'tree> {
     /// a new tree is created based on an older tree, and a node from the old
     /// tree is reused in the process, then that node will have the same id in
     /// both trees.
+    ///
+    /// Cloning a [Tree] shares all of its nodes, so every node except the root
+    /// has the same id in the original tree and in the clone. The root node's
+    /// id is different for each [Tree] value, so to correlate root nodes, compare
+    /// them by their byte range and kind instead.
     pub fn id(&self) -> usize {
         self.0.id as usize
     }
@@ -830,6 +1762,36 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_symbol(self.0) }
     }
 
+    /// Get the parse state in which the parser encountered this node.
+    ///
+    /// Returns `u16::MAX` if the node has no parse state.
+    #[doc(alias = "ts_node_parse_state")]
+    pub fn parse_state(&self) -> u16 {
+        unsafe { ffi::ts_node_parse_state(self.0) }
+    }
+
+    /// Get the parse state that the parser moved to after consuming this node.
+    ///
+    /// Pass this to [Language::lookahead_iterator] to find the symbols that could
+    /// have followed this node. Returns `u16::MAX` if the node has no parse state.
+    #[doc(alias = "ts_node_next_parse_state")]
+    pub fn next_parse_state(&self) -> u16 {
+        unsafe { ffi::ts_node_next_parse_state(self.0) }
+    }
+
+    /// Check if this node's type has the given numerical id.
+    ///
+    /// This avoids comparing strings when the id has been looked up ahead of
+    /// time using [Language::id_for_node_kind].
+    pub fn is_kind(&self, kind_id: u16) -> bool {
+        self.kind_id() == kind_id
+    }
+
+    /// Check if this node's type has any of the given numerical ids.
+    pub fn is_any_kind(&self, kind_ids: &[u16]) -> bool {
+        kind_ids.contains(&self.kind_id())
+    }
+
     /// Get this node's type as a string.
     #[doc(alias = "ts_node_type")]
     pub fn kind(&self) -> &'static str {
@@ -863,11 +1825,36 @@ impl<'tree> Node<'tree> {
     }
 
     /// Check if this node has been edited.
+    ///
+    /// This is only meaningful for a tree that has been edited with [Tree::edit],
+    /// before it is reparsed. The nodes of the new tree produced by reparsing are
+    /// never marked as changed.
     #[doc(alias = "ts_node_has_changes")]
     pub fn has_changes(&self) -> bool {
         unsafe { ffi::ts_node_has_changes(self.0) }
     }
 
+    /// Determine how this node's byte range relates to the region of the document that
+    /// was replaced by the given edit, from its `start_byte` to its `old_end_byte`.
+    ///
+    /// The node should come from a tree that has not yet been edited with `edit`. A node
+    /// whose range is exactly the edited region is [EditOverlap::Contained], and a node
+    /// that ends where an insertion starts is [EditOverlap::Before].
+    pub fn overlaps_edit(&self, edit: &InputEdit) -> EditOverlap {
+        let (start_byte, end_byte) = (self.start_byte(), self.end_byte());
+        if end_byte <= edit.start_byte {
+            EditOverlap::Before
+        } else if start_byte >= edit.old_end_byte {
+            EditOverlap::After
+        } else if edit.start_byte <= start_byte && end_byte <= edit.old_end_byte {
+            EditOverlap::Contained
+        } else if start_byte <= edit.start_byte && edit.old_end_byte <= end_byte {
+            EditOverlap::Contains
+        } else {
+            EditOverlap::Straddles
+        }
+    }
+
     /// Check if this node represents a syntax error or contains any syntax errors anywhere
     /// within it.
     #[doc(alias = "ts_node_has_error")]
@@ -892,6 +1879,29 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_is_missing(self.0) }
     }
 
+    /// Classify this node based on its flags.
+    ///
+    /// A node can have several of these properties at once, so the first of the
+    /// following that applies is returned:
+    /// 1. [NodeKind::Error] if the node is an error (see [Node::is_error])
+    /// 2. [NodeKind::Missing] if the node is missing (see [Node::is_missing])
+    /// 3. [NodeKind::Extra] if the node is extra (see [Node::is_extra])
+    /// 4. [NodeKind::Named] if the node is named (see [Node::is_named])
+    /// 5. [NodeKind::Anonymous] otherwise
+    pub fn kind_class(&self) -> NodeKind {
+        if self.is_error() {
+            NodeKind::Error
+        } else if self.is_missing() {
+            NodeKind::Missing
+        } else if self.is_extra() {
+            NodeKind::Extra
+        } else if self.is_named() {
+            NodeKind::Named
+        } else {
+            NodeKind::Anonymous
+        }
+    }
+
     /// Get the byte offsets where this node starts.
     #[doc(alias = "ts_node_start_byte")]
     pub fn start_byte(&self) -> usize {
@@ -927,6 +1937,56 @@ impl<'tree> Node<'tree> {
         result.into()
     }
 
+    /// Get the rows on which this node starts and ends, for use in code folding.
+    ///
+    /// See also [Tree::fold_ranges].
+    pub fn fold_range(&self) -> (usize, usize) {
+        (self.start_position().row, self.end_position().row)
+    }
+
+    /// Get the column at which this node starts, counted in characters rather
+    /// than bytes.
+    ///
+    /// The column in [Node::start_position] counts bytes, so it overstates the
+    /// visual column when the line contains multibyte UTF-8 characters before the
+    /// node. Each character counts as one column, including tabs; expanding tabs
+    /// to a tab width is left to the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the node's line is out of bounds for `source`.
+    pub fn start_column_chars(&self, source: &[u8]) -> usize {
+        let start_byte = self.start_byte();
+        let line_start = start_byte - self.start_position().column;
+        source[line_start..start_byte]
+            .iter()
+            .filter(|byte| (**byte & 0b1100_0000) != 0b1000_0000)
+            .count()
+    }
+
+    /// Get the full lines of source code that this node spans.
+    ///
+    /// The result extends from the start of the line on which the node starts to
+    /// the end of the line on which it ends, so for a multi-line node it contains
+    /// every line that the node touches. The terminating newline (`\n` or `\r\n`)
+    /// of the last line is not included. This is useful for printing diagnostics
+    /// with the node underlined.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node's range is out of bounds for `source`.
+    pub fn source_line<'a>(&self, source: &'a [u8]) -> &'a [u8] {
+        let start_byte = self.start_byte();
+        let line_start = start_byte - self.start_position().column;
+        let search_start = self.end_byte().saturating_sub(1).max(start_byte);
+        let line_end = source[search_start..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(source.len(), |i| search_start + i);
+        let line = &source[line_start..line_end];
+        line.strip_suffix(b"\r").unwrap_or(line)
+    }
+
     /// Get this node's end position in terms of rows and columns.
     #[doc(alias = "ts_node_end_point")]
     pub fn end_position(&self) -> Point {
@@ -986,6 +2046,17 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Follow a sequence of field names down from this node.
+    ///
+    /// Each name is looked up with [child_by_field_name](Node::child_by_field_name) on the
+    /// node found by the previous step. Returns `None` if any step has no such field. An
+    /// empty path returns this node.
+    pub fn field_path(&self, path: &[&str]) -> Option<Self> {
+        path.iter().try_fold(*self, |node, field_name| {
+            node.child_by_field_name(field_name)
+        })
+    }
+
     /// Get this node's child with the given numerical field id.
     ///
     /// See also [child_by_field_name](Node::child_by_field_name). You can convert a field name to
@@ -1091,12 +2162,124 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Iterate over this node's children of a given kind.
+    ///
+    /// Kinds are compared by name, so children whose kind has the same name but a
+    /// different id (for example because of an alias) are included.
+    ///
+    /// See also [Node::children].
+    pub fn children_of_kind<'a>(
+        &self,
+        kind: &'a str,
+        cursor: &'a mut TreeCursor<'tree>,
+    ) -> impl Iterator<Item = Node<'tree>> + 'a {
+        self.children(cursor)
+            .filter(move |child| child.kind() == kind)
+    }
+
+    /// Get this node's first child of a given kind.
+    ///
+    /// See also [Node::children_of_kind].
+    pub fn first_child_of_kind(&self, kind: &str) -> Option<Self> {
+        let mut cursor = self.walk();
+        let result = self.children_of_kind(kind, &mut cursor).next();
+        result
+    }
+
+    /// Get this node's last child of a given kind.
+    ///
+    /// See also [Node::children_of_kind].
+    pub fn last_child_of_kind(&self, kind: &str) -> Option<Self> {
+        let mut cursor = self.walk();
+        let result = self.children_of_kind(kind, &mut cursor).last();
+        result
+    }
+
+    /// Get this node's first child whose kind has the given numerical id.
+    ///
+    /// See also [Node::is_kind].
+    pub fn child_of_kind_id(&self, kind_id: u16) -> Option<Self> {
+        let mut cursor = self.walk();
+        let result = self
+            .children(&mut cursor)
+            .find(|child| child.is_kind(kind_id));
+        result
+    }
+
     /// Get this node's immediate parent.
     #[doc(alias = "ts_node_parent")]
     pub fn parent(&self) -> Option<Self> {
         Self::new(unsafe { ffi::ts_node_parent(self.0) })
     }
 
+    /// Get the numerical kind ids of this node's ancestors, starting with its
+    /// parent and ending with the root node.
+    ///
+    /// See also [Node::has_ancestor_of_kind].
+    pub fn ancestor_kinds(&self) -> Vec<u16> {
+        iter::successors(self.parent(), Node::parent)
+            .map(|node| node.kind_id())
+            .collect()
+    }
+
+    /// Check if any of this node's ancestors has the given numerical kind id.
+    ///
+    /// This stops walking up the tree as soon as a matching ancestor is found.
+    pub fn has_ancestor_of_kind(&self, kind_id: u16) -> bool {
+        iter::successors(self.parent(), Node::parent).any(|node| node.is_kind(kind_id))
+    }
+
+    /// Check if this node and another node belong to the same [Tree] value.
+    ///
+    /// Node equality only compares the nodes' ids, which are shared by a tree and
+    /// its clones, and by trees that reuse nodes from an older tree during an
+    /// incremental parse. Use this method to ensure that two nodes come from the
+    /// very same tree.
+    pub fn same_tree(&self, other: &Node) -> bool {
+        self.0.tree == other.0.tree
+    }
+
+    /// Get the root node of the syntax tree that this node belongs to.
+    ///
+    /// The root node borrows the same [Tree] as this node, so it has the same
+    /// `'tree` lifetime.
+    #[doc(alias = "ts_tree_root_node")]
+    pub fn tree_root(&self) -> Node<'tree> {
+        Node::new(unsafe { ffi::ts_tree_root_node(self.0.tree) }).unwrap()
+    }
+
+    /// Check if this node is the root node of its syntax tree.
+    ///
+    /// This is equivalent to checking that [Node::parent] returns `None`, but it
+    /// compares the node with its tree's root directly, rather than searching for the
+    /// node's parent.
+    pub fn is_root(&self) -> bool {
+        *self == self.tree_root()
+    }
+
+    /// Get the sequence of child indices that leads from the root node down to this node.
+    ///
+    /// Each index is suitable for passing to [Node::child], so anonymous children are
+    /// counted. Unlike [Node::id], the path can be stored and resolved against a
+    /// reparsed tree with [Tree::node_at_path]. This is best-effort: if the edits
+    /// changed the structure above this node, the path may lead to a different node,
+    /// or to none at all. The root node's path is empty.
+    pub fn path_from_root(&self) -> Vec<usize> {
+        let mut cursor = self.walk();
+        let mut path = Vec::new();
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            let index = parent
+                .children(&mut cursor)
+                .position(|child| child == node)
+                .unwrap();
+            path.push(index);
+            node = parent;
+        }
+        path.reverse();
+        path
+    }
+
     /// Get this node's next sibling.
     #[doc(alias = "ts_node_next_sibling")]
     pub fn next_sibling(&self) -> Option<Self> {
@@ -1121,70 +2304,515 @@ impl<'tree> Node<'tree> {
         Self::new(unsafe { ffi::ts_node_prev_named_sibling(self.0) })
     }
 
-    /// Get the smallest node within this node that spans the given range.
-    #[doc(alias = "ts_node_descendant_for_byte_range")]
-    pub fn descendant_for_byte_range(&self, start: usize, end: usize) -> Option<Self> {
-        Self::new(unsafe {
-            ffi::ts_node_descendant_for_byte_range(self.0, start as u32, end as u32)
-        })
+    /// Iterate over the siblings that follow this node, in order.
+    ///
+    /// This node itself is not included. See also [Node::next_sibling].
+    pub fn following_siblings(&self) -> impl Iterator<Item = Node<'tree>> {
+        iter::successors(self.next_sibling(), |node| node.next_sibling())
     }
 
-    /// Get the smallest named node within this node that spans the given range.
-    #[doc(alias = "ts_node_named_descendant_for_byte_range")]
-    pub fn named_descendant_for_byte_range(&self, start: usize, end: usize) -> Option<Self> {
-        Self::new(unsafe {
-            ffi::ts_node_named_descendant_for_byte_range(self.0, start as u32, end as u32)
+    /// Iterate over the siblings that follow this node, in order, using the given
+    /// cursor.
+    ///
+    /// This yields the same nodes as [Node::following_siblings], but each call to
+    /// [Node::next_sibling] searches from the parent node, whereas this walks the
+    /// cursor across the parent's children once, so it is much faster for nodes with
+    /// many siblings. This node itself is not included.
+    pub fn siblings<'a>(
+        &self,
+        cursor: &'a mut TreeCursor<'tree>,
+    ) -> impl Iterator<Item = Node<'tree>> + 'a {
+        let node = *self;
+        let mut done = true;
+        if let Some(parent) = self.parent() {
+            cursor.reset(parent);
+            if cursor.goto_first_child() {
+                while cursor.node() != node && cursor.goto_next_sibling() {}
+                done = cursor.node() != node;
+            }
+        }
+        iter::from_fn(move || {
+            if done || !cursor.goto_next_sibling() {
+                done = true;
+                return None;
+            }
+            Some(cursor.node())
         })
     }
 
-    /// Get the smallest node within this node that spans the given range.
-    #[doc(alias = "ts_node_descendant_for_point_range")]
-    pub fn descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
-        Self::new(unsafe {
-            ffi::ts_node_descendant_for_point_range(self.0, start.into(), end.into())
-        })
+    /// Iterate over the siblings that precede this node, starting with the
+    /// nearest one and moving backward.
+    ///
+    /// This node itself is not included. See also [Node::prev_sibling].
+    pub fn preceding_siblings(&self) -> impl Iterator<Item = Node<'tree>> {
+        iter::successors(self.prev_sibling(), |node| node.prev_sibling())
     }
 
-    /// Get the smallest named node within this node that spans the given range.
-    #[doc(alias = "ts_node_named_descendant_for_point_range")]
-    pub fn named_descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
-        Self::new(unsafe {
-            ffi::ts_node_named_descendant_for_point_range(self.0, start.into(), end.into())
-        })
+    /// Iterate over the *named* siblings that follow this node, in order.
+    ///
+    /// This node itself is not included. See also [Node::next_named_sibling].
+    pub fn following_named_siblings(&self) -> impl Iterator<Item = Node<'tree>> {
+        iter::successors(self.next_named_sibling(), |node| node.next_named_sibling())
     }
 
-    #[doc(alias = "ts_node_string")]
-    pub fn to_sexp(&self) -> String {
-        let c_string = unsafe { ffi::ts_node_string(self.0) };
-        let result = unsafe { CStr::from_ptr(c_string) }
-            .to_str()
-            .unwrap()
-            .to_string();
-        unsafe { (FREE_FN)(c_string as *mut c_void) };
-        result
+    /// Iterate over the *named* siblings that precede this node, starting with
+    /// the nearest one and moving backward.
+    ///
+    /// This node itself is not included. See also [Node::prev_named_sibling].
+    pub fn preceding_named_siblings(&self) -> impl Iterator<Item = Node<'tree>> {
+        iter::successors(self.prev_named_sibling(), |node| node.prev_named_sibling())
     }
 
-    pub fn utf8_text<'a>(&self, source: &'a [u8]) -> Result<&'a str, str::Utf8Error> {
-        str::from_utf8(&source[self.start_byte()..self.end_byte()])
+    /// Iterate over the descendants of this node that overlap the given byte
+    /// range, in pre-order.
+    ///
+    /// A node overlaps the range if it starts before the range's end and ends
+    /// after the range's start. Subtrees that lie entirely outside of the range
+    /// are skipped without being visited. This node itself is not included.
+    pub fn descendants_in_range(
+        &self,
+        range: ops::Range<usize>,
+    ) -> impl Iterator<Item = Node<'tree>> {
+        let mut cursor = self.walk();
+        let mut depth = 0usize;
+        let mut descend = true;
+        iter::from_fn(move || loop {
+            if descend && cursor.goto_first_child_for_byte(range.start).is_some() {
+                depth += 1;
+            } else {
+                loop {
+                    if depth == 0 {
+                        return None;
+                    }
+                    if cursor.goto_next_sibling() {
+                        break;
+                    }
+                    cursor.goto_parent();
+                    depth -= 1;
+                }
+            }
+
+            let node = cursor.node();
+            if node.start_byte() >= range.end {
+                // This node and its following siblings are all past the range.
+                cursor.goto_parent();
+                depth -= 1;
+                descend = false;
+            } else if node.end_byte() <= range.start {
+                descend = false;
+            } else {
+                descend = true;
+                return Some(node);
+            }
+        })
     }
 
-    pub fn utf16_text<'a>(&self, source: &'a [u16]) -> &'a [u16] {
-        &source.as_ref()[self.start_byte()..self.end_byte()]
+    /// Iterate over the descendants of this node that have been edited, in
+    /// pre-order.
+    ///
+    /// Subtrees that have not been edited are skipped without being visited.
+    /// Because every ancestor of an edited node is also considered edited, the
+    /// shallowest changed nodes are yielded before the more specific nodes within
+    /// them. See [Node::has_changes]. This node itself is not included.
+    pub fn changed_descendants(&self) -> impl Iterator<Item = Node<'tree>> {
+        let mut cursor = self.walk();
+        let mut depth = 0usize;
+        let mut descend = self.has_changes();
+        iter::from_fn(move || loop {
+            if descend && cursor.goto_first_child() {
+                depth += 1;
+            } else {
+                loop {
+                    if depth == 0 {
+                        return None;
+                    }
+                    if cursor.goto_next_sibling() {
+                        break;
+                    }
+                    cursor.goto_parent();
+                    depth -= 1;
+                }
+            }
+
+            let node = cursor.node();
+            descend = node.has_changes();
+            if descend {
+                return Some(node);
+            }
+        })
     }
 
-    /// Create a new [TreeCursor] starting from this node.
-    #[doc(alias = "ts_tree_cursor_new")]
-    pub fn walk(&self) -> TreeCursor<'tree> {
-        TreeCursor(unsafe { ffi::ts_tree_cursor_new(self.0) }, PhantomData)
+    /// Iterate over all of the descendants of this node in pre-order, along with
+    /// the name of the field that attaches each one to its parent and its depth.
+    ///
+    /// The children of this node have depth 1. Descendants that are not assigned
+    /// to a field have a field name of `None`. This node itself is not included.
+    pub fn walk_with_fields(
+        &self,
+    ) -> impl Iterator<Item = (Option<&'static str>, Node<'tree>, usize)> {
+        let mut cursor = self.walk();
+        let mut depth = 0usize;
+        iter::from_fn(move || {
+            if cursor.goto_first_child() {
+                depth += 1;
+            } else {
+                loop {
+                    if depth == 0 {
+                        return None;
+                    }
+                    if cursor.goto_next_sibling() {
+                        break;
+                    }
+                    cursor.goto_parent();
+                    depth -= 1;
+                }
+            }
+            Some((cursor.field_name(), cursor.node(), depth))
+        })
     }
 
-    /// Edit this node to keep it in-sync with source code that has been edited.
+    /// Find the first node, in pre-order, among this node and its descendants for
+    /// which the given predicate returns `true`.
     ///
-    /// This function is only rarely needed. When you edit a syntax tree with the
-    /// [Tree::edit] method, all of the nodes that you retrieve from the tree
-    /// afterward will already reflect the edit. You only need to use [Node::edit]
-    /// when you have a specific [Node] instance that you want to keep and continue
+    /// The walk stops as soon as a matching node is found.
+    pub fn find_first(
+        &self,
+        mut predicate: impl FnMut(&Node<'tree>) -> bool,
+    ) -> Option<Node<'tree>> {
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if predicate(&node) {
+                return Some(node);
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Collect all of the nodes, in pre-order, among this node and its descendants for
+    /// which the given predicate returns `true`.
+    pub fn find_all(&self, mut predicate: impl FnMut(&Node<'tree>) -> bool) -> Vec<Node<'tree>> {
+        let mut result = Vec::new();
+        self.find_first(|node| {
+            if predicate(node) {
+                result.push(*node);
+            }
+            false
+        });
+        result
+    }
+
+    /// Walk this node and its descendants in pre-order, calling the visitor's
+    /// [enter](TreeVisitor::enter) and [leave](TreeVisitor::leave) methods for each one.
+    ///
+    /// This node has depth 0 and a field name of `None`. If `enter` returns
+    /// [Descend::Skip], the node's children are not visited.
+    pub fn visit(&self, visitor: &mut impl TreeVisitor<'tree>) {
+        let mut cursor = self.walk();
+        let mut depth = 0usize;
+        loop {
+            let node = cursor.node();
+            if visitor.enter(node, cursor.field_name(), depth) == Descend::Children
+                && cursor.goto_first_child()
+            {
+                depth += 1;
+                continue;
+            }
+            visitor.leave(node);
+            loop {
+                if depth == 0 {
+                    return;
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                cursor.goto_parent();
+                depth -= 1;
+                visitor.leave(cursor.node());
+            }
+        }
+    }
+
+    /// Get the smallest node within this node that spans the given range.
+    ///
+    /// Returns `None` if `start` is greater than `end`. If no descendant contains the
+    /// range, including when the range extends outside of this node, this node itself
+    /// is returned.
+    #[doc(alias = "ts_node_descendant_for_byte_range")]
+    pub fn descendant_for_byte_range(&self, start: usize, end: usize) -> Option<Self> {
+        if start > end {
+            return None;
+        }
+        Self::new(unsafe {
+            ffi::ts_node_descendant_for_byte_range(self.0, start as u32, end as u32)
+        })
+    }
+
+    /// Get the smallest named node within this node that spans the given range.
+    ///
+    /// Returns `None` if `start` is greater than `end`. If no descendant contains the
+    /// range, including when the range extends outside of this node, this node itself
+    /// is returned.
+    #[doc(alias = "ts_node_named_descendant_for_byte_range")]
+    pub fn named_descendant_for_byte_range(&self, start: usize, end: usize) -> Option<Self> {
+        if start > end {
+            return None;
+        }
+        Self::new(unsafe {
+            ffi::ts_node_named_descendant_for_byte_range(self.0, start as u32, end as u32)
+        })
+    }
+
+    /// Get the smallest node within this node that spans the given range.
+    ///
+    /// Returns `None` if `start` is after `end`. If no descendant contains the range,
+    /// including when the range extends outside of this node, this node itself is
+    /// returned.
+    #[doc(alias = "ts_node_descendant_for_point_range")]
+    pub fn descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
+        if start > end {
+            return None;
+        }
+        Self::new(unsafe {
+            ffi::ts_node_descendant_for_point_range(self.0, start.into(), end.into())
+        })
+    }
+
+    /// Get the smallest named node within this node that spans the given range.
+    ///
+    /// This node does not need to be the root of its tree, so this can be used to
+    /// search within an arbitrary subtree, for example to expand a selection.
+    ///
+    /// Returns `None` if `start` is after `end`. If no descendant contains the range,
+    /// including when the range extends outside of this node, this node itself is
+    /// returned.
+    #[doc(alias = "ts_node_named_descendant_for_point_range")]
+    pub fn named_descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
+        if start > end {
+            return None;
+        }
+        Self::new(unsafe {
+            ffi::ts_node_named_descendant_for_point_range(self.0, start.into(), end.into())
+        })
+    }
+
+    /// Get the largest named node, among this node and its descendants, whose byte
+    /// range lies entirely within the range from `start` to `end`.
+    ///
+    /// This is the counterpart of [Node::named_descendant_for_byte_range], which gets
+    /// the smallest node that contains a range. When several nodes lie within the range
+    /// without being nested inside each other, such as consecutive statements in a
+    /// selection, the one that spans the most bytes is returned, and ties are broken in
+    /// favor of the earliest one.
+    ///
+    /// Returns `None` if no named node lies within the range.
+    pub fn innermost_contained_in(&self, start: usize, end: usize) -> Option<Self> {
+        let mut result: Option<Self> = None;
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            let is_contained = start <= node.start_byte() && node.end_byte() <= end;
+            let mut descend = false;
+            if is_contained && node.is_named() {
+                // None of this node's descendants can be larger, so skip them.
+                let len = node.end_byte() - node.start_byte();
+                let is_larger = match result {
+                    Some(largest) => len > largest.end_byte() - largest.start_byte(),
+                    None => true,
+                };
+                if is_larger {
+                    result = Some(node);
+                }
+            } else {
+                descend = is_contained || (node.start_byte() < end && node.end_byte() > start);
+            }
+
+            if descend && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Get the deepest node within this node, named or anonymous, at the given
+    /// position.
+    ///
+    /// When the position lies exactly on the boundary between two adjacent nodes,
+    /// the node that *starts* at the position is returned, rather than the one
+    /// that ends there. If no descendant extends past the position, as at the end
+    /// of this node or outside of it, then this node itself is returned.
+    pub fn node_at_point(&self, point: Point) -> Option<Self> {
+        self.descendant_for_point_range(point, point)
+    }
+
+    /// Get the deepest named node within this node at the given position.
+    ///
+    /// Boundaries are handled in the same way as in [Node::node_at_point].
+    pub fn named_node_at_point(&self, point: Point) -> Option<Self> {
+        self.named_descendant_for_point_range(point, point)
+    }
+
+    #[doc(alias = "ts_node_string")]
+    pub fn to_sexp(&self) -> String {
+        let c_string = unsafe { ffi::ts_node_string(self.0) };
+        let result = unsafe { CStr::from_ptr(c_string) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { (FREE_FN)(c_string as *mut c_void) };
+        result
+    }
+
+    /// Get an S-expression representing the node, like [Node::to_sexp], but with the
+    /// byte range of each node, as in `(identifier [0, 5])`.
+    ///
+    /// This makes it easier to correlate the nodes with the source code when debugging
+    /// a grammar.
+    pub fn to_sexp_with_ranges(&self) -> String {
+        let mut result = String::new();
+        let mut cursor = self.walk();
+
+        // Whether each ancestor of the cursor's current node was written out, and so
+        // needs a closing parenthesis.
+        let mut open = Vec::new();
+        loop {
+            let node = cursor.node();
+            let is_visible = open.is_empty() || node.is_named() || node.is_missing();
+            if is_visible {
+                if !open.is_empty() {
+                    result.push(' ');
+                    if let Some(field_name) = cursor.field_name() {
+                        result.push_str(field_name);
+                        result.push_str(": ");
+                    }
+                }
+                result.push('(');
+                if node.is_missing() {
+                    result.push_str("MISSING ");
+                }
+                if node.is_named() {
+                    result.push_str(node.kind());
+                } else {
+                    result.push_str(&format!("\"{}\"", node.kind()));
+                }
+                result.push_str(&format!(" [{}, {}]", node.start_byte(), node.end_byte()));
+            }
+
+            if cursor.goto_first_child() {
+                open.push(is_visible);
+                continue;
+            }
+            if is_visible {
+                result.push(')');
+            }
+            loop {
+                if open.is_empty() {
+                    return result;
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                cursor.goto_parent();
+                if open.pop() == Some(true) {
+                    result.push(')');
+                }
+            }
+        }
+    }
+
+    /// Get the text of this node from the source code that was used to parse it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node's byte range is out of bounds for `source`, which usually
+    /// means that `source` does not match the tree. See [Node::utf8_text_checked].
+    pub fn utf8_text<'a>(&self, source: &'a [u8]) -> Result<&'a str, str::Utf8Error> {
+        debug_assert!(
+            self.end_byte() <= source.len(),
+            "node's byte range {:?} is out of bounds for source of length {}; \
+             the source does not match the tree",
+            self.byte_range(),
+            source.len(),
+        );
+        str::from_utf8(&source[self.start_byte()..self.end_byte()])
+    }
+
+    /// Get the text of this node from the source code that was used to parse it,
+    /// returning `None` if the node's byte range is out of bounds for `source`.
+    pub fn utf8_text_checked<'a>(
+        &self,
+        source: &'a [u8],
+    ) -> Option<Result<&'a str, str::Utf8Error>> {
+        source.get(self.byte_range()).map(str::from_utf8)
+    }
+
+    /// Check if the text of this node in the given source code is equal to `text`.
+    ///
+    /// The bytes are compared directly, without validating them as UTF-8. Returns
+    /// `false` if the node's byte range is out of bounds for `source`.
+    pub fn text_eq(&self, source: &[u8], text: &str) -> bool {
+        source.get(self.byte_range()) == Some(text.as_bytes())
+    }
+
+    pub fn utf16_text<'a>(&self, source: &'a [u16]) -> &'a [u16] {
+        &source.as_ref()[self.start_byte()..self.end_byte()]
+    }
+
+    /// Get the text of this node by concatenating the chunks returned by a
+    /// [TextProvider].
+    ///
+    /// Unlike [Node::utf8_text], this does not require the source code to be
+    /// stored in one contiguous slice, so it can be used with documents that are
+    /// stored in a rope or other chunked data structure.
+    pub fn text_with_provider<'a>(&self, mut text_provider: impl TextProvider<'a>) -> Vec<u8> {
+        let mut result = Vec::with_capacity(self.end_byte() - self.start_byte());
+        for chunk in text_provider.text(*self) {
+            result.extend_from_slice(&chunk);
+        }
+        result
+    }
+
+    /// Get the text of this node from a [TextProvider], without copying it when
+    /// possible.
+    ///
+    /// If the provider returns the node's text as a single chunk, that chunk is
+    /// returned as-is, so text that borrows from the source code is not copied.
+    /// Otherwise, the chunks are concatenated into an owned buffer.
+    pub fn text_cow<'a>(&self, mut text_provider: impl TextProvider<'a>) -> Cow<'a, [u8]> {
+        join_chunks(
+            text_provider.text(*self),
+            self.end_byte() - self.start_byte(),
+        )
+    }
+
+    /// Create a new [TreeCursor] starting from this node.
+    #[doc(alias = "ts_tree_cursor_new")]
+    pub fn walk(&self) -> TreeCursor<'tree> {
+        TreeCursor(unsafe { ffi::ts_tree_cursor_new(self.0) }, PhantomData)
+    }
+
+    /// Edit this node to keep it in-sync with source code that has been edited.
+    ///
+    /// This function is only rarely needed. When you edit a syntax tree with the
+    /// [Tree::edit] method, all of the nodes that you retrieve from the tree
+    /// afterward will already reflect the edit. You only need to use [Node::edit]
+    /// when you have a specific [Node] instance that you want to keep and continue
     /// to use after an edit.
+    ///
+    /// Because [Node] is [Copy], this only updates the position of this particular
+    /// value. The tree that the node belongs to, and any other copies of the node,
+    /// are left unchanged.
     #[doc(alias = "ts_node_edit")]
     pub fn edit(&mut self, edit: &InputEdit) {
         let edit = edit.into();
@@ -1269,6 +2897,18 @@ impl<'a> TreeCursor<'a> {
         return unsafe { ffi::ts_tree_cursor_goto_first_child(&mut self.0) };
     }
 
+    /// Move this cursor to the last child of its current node.
+    ///
+    /// This returns `true` if the cursor successfully moved, and returns `false`
+    /// if there were no children.
+    pub fn goto_last_child(&mut self) -> bool {
+        if !self.goto_first_child() {
+            return false;
+        }
+        while self.goto_next_sibling() {}
+        true
+    }
+
     /// Move this cursor to the parent of its current node.
     ///
     /// This returns `true` if the cursor successfully moved, and returns `false`
@@ -1346,9 +2986,123 @@ impl Query {
     /// on syntax nodes parsed with that language. References to Queries can be
     /// shared between multiple threads.
     pub fn new(language: Language, source: &str) -> Result<Self, QueryError> {
+        Self::from_bytes(language, source.as_bytes())
+    }
+
+    /// Create a new query that can use the custom predicates in the given registry.
+    ///
+    /// The arguments of each registered predicate are validated when the query is
+    /// created, and the predicates are evaluated automatically along with the
+    /// built-in text predicates when iterating over matches and captures.
+    /// Predicates that are not registered are available via
+    /// [Query::general_predicates], as usual.
+    pub fn new_with_predicates(
+        language: Language,
+        source: &str,
+        registry: &PredicateRegistry,
+    ) -> Result<Self, QueryError> {
+        Self::compile(language, source.as_bytes(), registry)
+    }
+
+    /// Create a new query from a byte slice containing one or more S-expression
+    /// patterns.
+    ///
+    /// This behaves like [Query::new], but does not require the source to be
+    /// validated as UTF-8 beforehand.
+    #[doc(alias = "ts_query_new")]
+    pub fn from_bytes(language: Language, bytes: &[u8]) -> Result<Self, QueryError> {
+        Self::compile(language, bytes, &PredicateRegistry::default())
+    }
+
+    /// Split a query source string into the source of its individual top-level
+    /// patterns, without compiling it.
+    ///
+    /// Each pattern includes its trailing quantifiers and captures, and any field
+    /// name that precedes it. Comments and the whitespace between patterns are
+    /// omitted, and parentheses within string literals and comments are ignored.
+    /// The source does not need to be valid, so this can be used to compile each
+    /// pattern separately and report exactly which patterns contain errors.
+    pub fn patterns(source: &str) -> Vec<&str> {
+        let bytes = source.as_bytes();
+        let mut result = Vec::new();
+        let mut pattern_start = None;
+        let mut pattern_end = 0;
+        let mut depth = 0usize;
+        let mut after_field_name = false;
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if byte.is_ascii_whitespace() {
+                i += 1;
+                continue;
+            }
+            if byte == b';' {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+
+            // At the top level, anything other than a suffix begins a new pattern,
+            // unless it follows a field name.
+            if depth == 0 && !matches!(byte, b')' | b']' | b':' | b'@' | b'*' | b'+' | b'?') {
+                if !after_field_name {
+                    if let Some(start) = pattern_start.replace(i) {
+                        result.push(&source[start..pattern_end]);
+                    }
+                }
+                after_field_name = false;
+            }
+            pattern_start.get_or_insert(i);
+
+            match byte {
+                b'(' | b'[' => {
+                    depth += 1;
+                    i += 1;
+                }
+                b')' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    i += 1;
+                }
+                b':' => {
+                    after_field_name = depth == 0;
+                    i += 1;
+                }
+                b'"' => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != b'"' {
+                        if bytes[i] == b'\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    i = (i + 1).min(bytes.len());
+                }
+                _ => {
+                    i += 1;
+                    while i < bytes.len()
+                        && !bytes[i].is_ascii_whitespace()
+                        && !b"()[]\";:@".contains(&bytes[i])
+                    {
+                        i += 1;
+                    }
+                }
+            }
+            pattern_end = i;
+        }
+        if let Some(start) = pattern_start {
+            result.push(&source[start..pattern_end]);
+        }
+        result
+    }
+
+    fn compile(
+        language: Language,
+        bytes: &[u8],
+        registry: &PredicateRegistry,
+    ) -> Result<Self, QueryError> {
         let mut error_offset = 0u32;
         let mut error_type: ffi::TSQueryError = 0;
-        let bytes = source.as_bytes();
 
         // Compile the query.
         let ptr = unsafe {
@@ -1380,7 +3134,7 @@ impl Query {
             let mut line_start = 0;
             let mut row = 0;
             let mut line_containing_error = None;
-            for line in source.split("\n") {
+            for line in bytes.split(|b| *b == b'\n') {
                 let line_end = line_start + line.len() + 1;
                 if line_end > offset {
                     line_containing_error = Some(line);
@@ -1398,11 +3152,14 @@ impl Query {
                 ffi::TSQueryError_TSQueryErrorNodeType
                 | ffi::TSQueryError_TSQueryErrorField
                 | ffi::TSQueryError_TSQueryErrorCapture => {
-                    let suffix = source.split_at(offset).1;
+                    let suffix = &bytes[offset..];
                     let end_offset = suffix
-                        .find(|c| !char::is_alphanumeric(c) && c != '_' && c != '-')
-                        .unwrap_or(source.len());
-                    message = suffix.split_at(end_offset).0.to_string();
+                        .iter()
+                        .position(|b| {
+                            b.is_ascii() && !b.is_ascii_alphanumeric() && *b != b'_' && *b != b'-'
+                        })
+                        .unwrap_or(suffix.len());
+                    message = String::from_utf8_lossy(&suffix[..end_offset]).into_owned();
                     kind = match error_type {
                         ffi::TSQueryError_TSQueryErrorNodeType => QueryErrorKind::NodeType,
                         ffi::TSQueryError_TSQueryErrorField => QueryErrorKind::Field,
@@ -1414,7 +3171,10 @@ impl Query {
                 // Error types that report positions
                 _ => {
                     message = if let Some(line) = line_containing_error {
-                        line.to_string() + "\n" + &" ".repeat(offset - line_start) + "^"
+                        String::from_utf8_lossy(line).into_owned()
+                            + "\n"
+                            + &" ".repeat(offset - line_start)
+                            + "^"
                     } else {
                         "Unexpected EOF".to_string()
                     };
@@ -1439,6 +3199,11 @@ impl Query {
         let pattern_count = unsafe { ffi::ts_query_pattern_count(ptr) as usize };
         let mut result = Query {
             ptr: unsafe { NonNull::new_unchecked(ptr) },
+            language,
+            source: bytes.into(),
+            disabled_captures: Vec::new(),
+            disabled_patterns: Vec::new(),
+            predicate_registry: registry.clone(),
             capture_names: Vec::with_capacity(capture_count as usize),
             capture_quantifiers: Vec::with_capacity(pattern_count as usize),
             text_predicates: Vec::with_capacity(pattern_count),
@@ -1454,8 +3219,9 @@ impl Query {
                 let name =
                     ffi::ts_query_capture_name_for_id(ptr, i, &mut length as *mut u32) as *const u8;
                 let name = slice::from_raw_parts(name, length as usize);
-                let name = str::from_utf8_unchecked(name);
-                result.capture_names.push(name.to_string());
+                result
+                    .capture_names
+                    .push(String::from_utf8_lossy(name).into_owned());
             }
         }
 
@@ -1479,8 +3245,7 @@ impl Query {
                     ffi::ts_query_string_value_for_id(ptr, i as u32, &mut length as *mut u32)
                         as *const u8;
                 let value = slice::from_raw_parts(value, length as usize);
-                let value = str::from_utf8_unchecked(value);
-                value.to_string()
+                String::from_utf8_lossy(value).into_owned()
             })
             .collect::<Vec<_>>();
 
@@ -1498,10 +3263,9 @@ impl Query {
             };
 
             let byte_offset = unsafe { ffi::ts_query_start_byte_for_pattern(ptr, i as u32) };
-            let row = source
-                .char_indices()
-                .take_while(|(i, _)| *i < byte_offset as usize)
-                .filter(|(_, c)| *c == '\n')
+            let row = bytes[..byte_offset as usize]
+                .iter()
+                .filter(|b| **b == b'\n')
                 .count();
 
             let type_done = ffi::TSQueryPredicateStepType_TSQueryPredicateStepTypeDone;
@@ -1512,6 +3276,7 @@ impl Query {
             let mut property_predicates = Vec::new();
             let mut property_settings = Vec::new();
             let mut general_predicates = Vec::new();
+            let mut match_predicate_count = 0;
             for p in predicate_steps.split(|s| s.type_ == type_done) {
                 if p.is_empty() {
                     continue;
@@ -1563,6 +3328,39 @@ impl Query {
                         });
                     }
 
+                    "eq-ci?" | "not-eq-ci?" => {
+                        if p.len() != 3 {
+                            return Err(predicate_error(
+                                row,
+                                format!(
+                                "Wrong number of arguments to #eq-ci? predicate. Expected 2, got {}.",
+                                p.len() - 1
+                            ),
+                            ));
+                        }
+                        if p[1].type_ != type_capture {
+                            return Err(predicate_error(row, format!(
+                                "First argument to #eq-ci? predicate must be a capture name. Got literal \"{}\".",
+                                string_values[p[1].value_id as usize],
+                            )));
+                        }
+
+                        let is_positive = operator_name == "eq-ci?";
+                        text_predicates.push(if p[2].type_ == type_capture {
+                            TextPredicate::CaptureEqCaptureCi(
+                                p[1].value_id,
+                                p[2].value_id,
+                                is_positive,
+                            )
+                        } else {
+                            TextPredicate::CaptureEqStringCi(
+                                p[1].value_id,
+                                string_values[p[2].value_id as usize].clone(),
+                                is_positive,
+                            )
+                        });
+                    }
+
                     "match?" | "not-match?" => {
                         if p.len() != 3 {
                             return Err(predicate_error(row, format!(
@@ -1588,10 +3386,28 @@ impl Query {
                         text_predicates.push(TextPredicate::CaptureMatchString(
                             p[1].value_id,
                             regex::bytes::Regex::new(regex).map_err(|_| {
-                                predicate_error(row, format!("Invalid regex '{}'", regex))
+                                let message = format!("Invalid regex '{}'", regex);
+                                match regex_offset(
+                                    bytes,
+                                    byte_offset as usize,
+                                    match_predicate_count,
+                                ) {
+                                    Some(offset) => {
+                                        let point = byte_to_point(bytes, offset);
+                                        QueryError {
+                                            kind: QueryErrorKind::Predicate,
+                                            row: point.row,
+                                            column: point.column,
+                                            offset,
+                                            message,
+                                        }
+                                    }
+                                    None => predicate_error(row, message),
+                                }
                             })?,
                             is_positive,
                         ));
+                        match_predicate_count += 1;
                     }
 
                     "set!" => property_settings.push(Self::parse_property(
@@ -1613,21 +3429,34 @@ impl Query {
                         operator_name == "is?",
                     )),
 
-                    _ => general_predicates.push(QueryPredicate {
-                        operator: operator_name.clone().into_boxed_str(),
-                        args: p[1..]
-                            .iter()
-                            .map(|a| {
-                                if a.type_ == type_capture {
-                                    QueryPredicateArg::Capture(a.value_id)
-                                } else {
-                                    QueryPredicateArg::String(
-                                        string_values[a.value_id as usize].clone().into_boxed_str(),
-                                    )
-                                }
-                            })
-                            .collect(),
-                    }),
+                    _ => {
+                        let predicate = QueryPredicate {
+                            operator: operator_name.clone().into_boxed_str(),
+                            args: p[1..]
+                                .iter()
+                                .map(|a| {
+                                    if a.type_ == type_capture {
+                                        QueryPredicateArg::Capture(a.value_id)
+                                    } else {
+                                        QueryPredicateArg::String(
+                                            string_values[a.value_id as usize]
+                                                .clone()
+                                                .into_boxed_str(),
+                                        )
+                                    }
+                                })
+                                .collect(),
+                        };
+                        match registry.predicates.get(operator_name) {
+                            Some(registered) => {
+                                (registered.validate)(&predicate)
+                                    .map_err(|message| predicate_error(row, message))?;
+                                text_predicates
+                                    .push(TextPredicate::Custom(predicate, registered.clone()));
+                            }
+                            None => general_predicates.push(predicate),
+                        }
+                    }
                 }
             }
 
@@ -1678,6 +3507,25 @@ impl Query {
         &self.capture_quantifiers[index]
     }
 
+    /// Check that every capture name used in the query is one of the given
+    /// allowed names.
+    ///
+    /// Returns the names of any captures that are not allowed, in the order in
+    /// which they first appear in the query.
+    pub fn validate_captures(&self, allowed: &[&str]) -> Result<(), Vec<String>> {
+        let unknown = self
+            .capture_names
+            .iter()
+            .filter(|name| !allowed.contains(&name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(unknown)
+        }
+    }
+
     /// Get the index for a given capture name.
     pub fn capture_index_for_name(&self, name: &str) -> Option<u32> {
         self.capture_names
@@ -1695,7 +3543,10 @@ impl Query {
 
     /// Get the properties that are set for the given pattern index.
     ///
-    /// This includes predicates with the operator `set!`.
+    /// This includes predicates with the operator `set!`. The key may be
+    /// followed by a value, as in `(#set! injection.language "rust")`, or may
+    /// stand alone to set a boolean flag, as in `(#set! conceal)`. In the latter
+    /// case, the property's `value` is `None`.
     pub fn property_settings(&self, index: usize) -> &[QueryProperty] {
         &self.property_settings[index]
     }
@@ -1705,12 +3556,77 @@ impl Query {
     /// This includes predicate with operators other than:
     /// * `match?`
     /// * `eq?` and `not-eq?`
+    /// * `eq-ci?` and `not-eq-ci?`, which compare text ignoring ASCII case. Non-ASCII
+    ///   characters must match exactly.
     /// * `is?` and `is-not?`
     /// * `set!`
+    ///
+    /// It also excludes any custom predicates that were registered when the query
+    /// was created with [Query::new_with_predicates].
     pub fn general_predicates(&self, index: usize) -> &[QueryPredicate] {
         &self.general_predicates[index]
     }
 
+    /// Describe all of the predicates associated with the given pattern index,
+    /// in the order in which they appear in the query source.
+    ///
+    /// Unlike [Query::general_predicates], this includes predicates with the
+    /// built-in operators, such as `eq?`, `match?` and `set!`.
+    #[doc(alias = "ts_query_predicates_for_pattern")]
+    pub fn pattern_predicates(&self, index: usize) -> impl Iterator<Item = PredicateInfo> + '_ {
+        if index >= self.text_predicates.len() {
+            panic!(
+                "Pattern index is {} but the pattern count is {}",
+                index,
+                self.text_predicates.len(),
+            );
+        }
+
+        let steps = unsafe {
+            let mut length = 0u32;
+            let raw_predicates = ffi::ts_query_predicates_for_pattern(
+                self.ptr.as_ptr(),
+                index as u32,
+                &mut length as *mut u32,
+            );
+            if length > 0 {
+                slice::from_raw_parts(raw_predicates, length as usize)
+            } else {
+                &[]
+            }
+        };
+
+        let string_value = move |id: u32| unsafe {
+            let mut length = 0u32;
+            let value =
+                ffi::ts_query_string_value_for_id(self.ptr.as_ptr(), id, &mut length as *mut u32)
+                    as *const u8;
+            let value = slice::from_raw_parts(value, length as usize);
+            String::from_utf8_lossy(value).into()
+        };
+
+        steps
+            .split(|s| s.type_ == ffi::TSQueryPredicateStepType_TSQueryPredicateStepTypeDone)
+            .filter(|p| !p.is_empty())
+            .map(move |p| PredicateInfo {
+                operator: string_value(p[0].value_id),
+                args: p[1..]
+                    .iter()
+                    .map(|a| {
+                        if a.type_ == ffi::TSQueryPredicateStepType_TSQueryPredicateStepTypeCapture
+                        {
+                            PredicateInfoArg::Capture {
+                                index: a.value_id,
+                                name: self.capture_names[a.value_id as usize].as_str().into(),
+                            }
+                        } else {
+                            PredicateInfoArg::String(string_value(a.value_id))
+                        }
+                    })
+                    .collect(),
+            })
+    }
+
     /// Disable a certain capture within a query.
     ///
     /// This prevents the capture from being returned in matches, and also avoids any
@@ -1724,6 +3640,7 @@ impl Query {
                 name.len() as u32,
             );
         }
+        self.disabled_captures.push(name.to_string());
     }
 
     /// Disable a certain pattern within a query.
@@ -1733,6 +3650,7 @@ impl Query {
     #[doc(alias = "ts_query_disable_pattern")]
     pub fn disable_pattern(&mut self, index: usize) {
         unsafe { ffi::ts_query_disable_pattern(self.ptr.as_ptr(), index as u32) }
+        self.disabled_patterns.push(index);
     }
 
     /// Check if a given pattern within a query has a single root node.
@@ -1832,6 +3750,11 @@ impl QueryCursor {
         }
     }
 
+    /// Create a builder for configuring a new cursor's ranges and match limit.
+    pub fn builder() -> QueryCursorBuilder {
+        QueryCursorBuilder::default()
+    }
+
     /// Return the maximum number of in-progress matches for this cursor.
     #[doc(alias = "ts_query_cursor_match_limit")]
     pub fn match_limit(&self) -> u32 {
@@ -1878,10 +3801,224 @@ impl QueryCursor {
         }
     }
 
+    /// Iterate over the matches in the order that they were found, stopping once the
+    /// given deadline has passed.
+    ///
+    /// The clock is checked before every few matches, so the iterator may yield a small
+    /// number of matches after the deadline, and finding a single match is never
+    /// interrupted. When the deadline is hit, the results are partial, and
+    /// [DeadlineQueryMatches::did_exceed_deadline] returns `true`, in the same way that
+    /// [QueryCursor::did_exceed_match_limit] reports that matches may have been dropped.
+    #[doc(alias = "ts_query_cursor_exec")]
+    pub fn matches_with_deadline<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        text_provider: T,
+        deadline: Instant,
+    ) -> DeadlineQueryMatches<'a, 'tree, T> {
+        DeadlineQueryMatches {
+            matches: self.matches(query, node, text_provider),
+            deadline,
+            matches_until_check: 0,
+            deadline_exceeded: false,
+        }
+    }
+
+    /// Iterate over all of the matches in the order that they were found, without
+    /// evaluating any of the query's predicates.
+    ///
+    /// This yields every match that the query engine produces, including those
+    /// that [QueryCursor::matches] would filter out because of predicates like
+    /// `#eq?` and `#match?`. It is useful for callers that want to handle
+    /// predicates themselves.
+    #[doc(alias = "ts_query_cursor_exec")]
+    pub fn matches_raw<'a, 'tree: 'a>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+    ) -> impl Iterator<Item = QueryMatch<'a, 'tree>> + 'a {
+        let ptr = self.ptr.as_ptr();
+        unsafe { ffi::ts_query_cursor_exec(ptr, query.ptr.as_ptr(), node.0) };
+        iter::from_fn(move || unsafe {
+            let mut m = MaybeUninit::<ffi::TSQueryMatch>::uninit();
+            if ffi::ts_query_cursor_next_match(ptr, m.as_mut_ptr()) {
+                Some(QueryMatch::new(m.assume_init(), ptr))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Iterate over all of the matches, like [QueryCursor::matches], but only
+    /// yield the matches whose general predicates are all accepted by the given
+    /// callback.
+    ///
+    /// The callback is invoked once for each of the match's pattern's
+    /// [general predicates](Query::general_predicates), after the built-in
+    /// predicates have already been checked. This allows custom predicates such
+    /// as `#has-ancestor?` to be implemented outside of this crate.
+    pub fn matches_with_predicate_filter<'a, 'tree: 'a, T, F>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        text_provider: T,
+        mut predicate_fn: F,
+    ) -> impl Iterator<Item = QueryMatch<'a, 'tree>> + 'a
+    where
+        T: TextProvider<'a> + 'a,
+        F: FnMut(&QueryMatch<'a, 'tree>, &QueryPredicate) -> bool + 'a,
+    {
+        self.matches(query, node, text_provider).filter(move |m| {
+            query.general_predicates[m.pattern_index]
+                .iter()
+                .all(|predicate| predicate_fn(m, predicate))
+        })
+    }
+
+    /// Run the query and collect all of its matches, in the order that they were
+    /// found.
+    ///
+    /// Unlike the matches returned by [QueryCursor::matches], the resulting matches
+    /// own their captures, so they can be sorted or stored after the cursor is
+    /// reused.
+    pub fn all_matches_owned<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        text_provider: T,
+    ) -> Vec<OwnedQueryMatch<'tree>> {
+        self.matches(query, node, text_provider)
+            .map(|m| OwnedQueryMatch {
+                pattern_index: m.pattern_index,
+                captures: m.captures.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Run the query only within the descendants of `node` whose kind id is one of `kinds`,
+    /// and collect all of the resulting matches.
+    ///
+    /// The query is executed separately on each outermost node of one of the given kinds,
+    /// so a match is only found if all of its nodes lie within such a node (including the
+    /// node itself). This avoids running the query over the rest of the tree, which can
+    /// be much faster on large files when only a few kinds of nodes are of interest.
+    ///
+    /// To scan a set of regions that are known in advance, call [QueryCursor::set_byte_range]
+    /// or [QueryCursor::set_point_range] before each call to [QueryCursor::matches] instead.
+    pub fn matches_filtered<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        kinds: &[u16],
+        mut text_provider: T,
+    ) -> Vec<OwnedQueryMatch<'tree>> {
+        let ptr = self.ptr.as_ptr();
+        let mut result = Vec::new();
+        let mut cursor = node.walk();
+        'outer: loop {
+            let descendant = cursor.node();
+            if kinds.contains(&descendant.kind_id()) {
+                unsafe { ffi::ts_query_cursor_exec(ptr, query.ptr.as_ptr(), descendant.0) };
+                let matches: QueryMatches<'a, 'tree, _> = QueryMatches {
+                    ptr,
+                    query,
+                    text_provider: |node: Node| text_provider.text(node),
+                    buffer1: Default::default(),
+                    buffer2: Default::default(),
+                    _tree: PhantomData,
+                };
+                result.extend(matches.map(|m| OwnedQueryMatch {
+                    pattern_index: m.pattern_index,
+                    captures: m.captures.to_vec(),
+                }));
+            } else if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'outer;
+                }
+            }
+        }
+        result
+    }
+
+    /// Run the query on `new_tree` only within the ranges whose syntactic structure has
+    /// changed since `old_tree`, and collect the resulting matches.
+    ///
+    /// `old_tree` must have been edited to match `new_tree`, as for [Tree::changed_ranges].
+    /// The changed ranges are merged with [Range::merge_all], and the query is executed
+    /// once for each of them, so any match that intersects one of the ranges is found. A
+    /// match that intersects several ranges is only returned once.
+    ///
+    /// The query is executed with a separate cursor, so this cursor's state is not
+    /// changed. That cursor uses this cursor's match limit and cancellation flag, but not
+    /// its byte or point range.
+    pub fn matches_in_changed_ranges<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &self,
+        query: &'a Query,
+        old_tree: &Tree,
+        new_tree: &'tree Tree,
+        mut text_provider: T,
+    ) -> Vec<OwnedQueryMatch<'tree>> {
+        let mut cursor = QueryCursor::new();
+        cursor.set_match_limit(self.match_limit());
+        let ptr = cursor.ptr.as_ptr();
+        unsafe {
+            ffi::ts_query_cursor_set_cancellation_flag(
+                ptr,
+                ffi::ts_query_cursor_cancellation_flag(self.ptr.as_ptr()),
+            );
+        }
+        let root = new_tree.root_node();
+        let mut result = Vec::new();
+        let mut seen = HashSet::new();
+        for range in Range::merge_all(old_tree.changed_ranges(new_tree)) {
+            if range.start_byte == range.end_byte {
+                continue;
+            }
+            cursor.set_byte_range(range.start_byte..range.end_byte);
+            unsafe { ffi::ts_query_cursor_exec(ptr, query.ptr.as_ptr(), root.0) };
+            let matches: QueryMatches<'a, 'tree, _> = QueryMatches {
+                ptr,
+                query,
+                text_provider: |node: Node| text_provider.text(node),
+                buffer1: Default::default(),
+                buffer2: Default::default(),
+                _tree: PhantomData,
+            };
+            for m in matches {
+                let key = (
+                    m.pattern_index,
+                    m.captures
+                        .iter()
+                        .map(|capture| (capture.node.id(), capture.index))
+                        .collect::<Vec<_>>(),
+                );
+                if seen.insert(key) {
+                    result.push(OwnedQueryMatch {
+                        pattern_index: m.pattern_index,
+                        captures: m.captures.to_vec(),
+                    });
+                }
+            }
+        }
+        result
+    }
+
     /// Iterate over all of the individual captures in the order that they appear.
     ///
     /// This is useful if you don't care about which pattern matched, and just want a single,
     /// ordered sequence of captures.
+    ///
+    /// Captures are ordered by the start position of their nodes. Captures that start at
+    /// the same position are ordered by pattern index, so when several patterns capture the
+    /// same node, the capture from the pattern that appears earliest in the query comes
+    /// first. Syntax highlighters give that capture precedence: use
+    /// [QueryCursor::captures_deduped] to drop the others, or
+    /// [QueryCursor::captures_by_priority] to also resolve captures that overlap without
+    /// covering the same range.
     #[doc(alias = "ts_query_cursor_exec")]
     pub fn captures<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
         &'a mut self,
@@ -1901,7 +4038,79 @@ impl QueryCursor {
         }
     }
 
+    /// Iterate over all of the individual captures in the order that they appear,
+    /// along with the index of the pattern that matched and the text of each
+    /// captured node.
+    ///
+    /// The text is obtained from the same [TextProvider] that is used to evaluate the
+    /// query's predicates. The text of a node that was already resolved to evaluate a
+    /// predicate is reused rather than requested again. As with [Node::text_cow], it is
+    /// not copied when the provider returns it as a single chunk.
+    pub fn captures_with_text<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        text_provider: T,
+    ) -> QueryCapturesWithText<'a, 'tree, T> {
+        let text_provider = TextCache {
+            text_provider,
+            texts: Vec::new(),
+        };
+        QueryCapturesWithText {
+            captures: self.captures(query, node, text_provider),
+        }
+    }
+
+    /// Iterate over the captures in the order that they appear, yielding at most one
+    /// capture for each distinct node range.
+    ///
+    /// When several captures cover the same byte range, only the first one is kept. For a
+    /// given node, captures are produced in order of pattern index, so this keeps the capture
+    /// from the pattern that appears *earliest* in the query, which is the same precedence
+    /// rule that syntax highlighting uses. Captures whose text predicates fail are never
+    /// considered.
+    pub fn captures_deduped<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        text_provider: T,
+    ) -> DedupedQueryCaptures<'a, 'tree, T> {
+        DedupedQueryCaptures {
+            captures: self.captures(query, node, text_provider),
+            seen_ranges: Vec::new(),
+        }
+    }
+
+    /// Iterate over the captures in the order that they appear, yielding, for each byte
+    /// that is covered by any capture, only the capture from the pattern with the lowest
+    /// index.
+    ///
+    /// This is the precedence rule that syntax highlighting uses: when patterns capture
+    /// overlapping ranges, the pattern that appears earliest in the query wins. Each item
+    /// contains the winning capture's pattern index, the capture, and the range of bytes
+    /// that it wins, which is part of the captured node's range. The ranges are disjoint
+    /// and in order, and a capture appears once for each separate range that it wins.
+    /// Among captures from the same pattern, the one that the query engine produces
+    /// first wins. Captures of empty nodes are skipped, and captures whose text
+    /// predicates fail are never considered.
+    pub fn captures_by_priority<'a, 'tree: 'a, T: TextProvider<'a> + 'a>(
+        &'a mut self,
+        query: &'a Query,
+        node: Node<'tree>,
+        text_provider: T,
+    ) -> PriorityQueryCaptures<'a, 'tree, T> {
+        PriorityQueryCaptures {
+            captures: self.captures(query, node, text_provider),
+            pending: None,
+            active: Vec::new(),
+            position: 0,
+        }
+    }
+
     /// Set the range in which the query will be executed, in terms of byte offsets.
+    ///
+    /// The range remains in effect for later executions of this cursor. An end of
+    /// zero means that the range extends to the end of the document.
     #[doc(alias = "ts_query_cursor_set_byte_range")]
     pub fn set_byte_range(&mut self, range: ops::Range<usize>) -> &mut Self {
         unsafe {
@@ -1915,6 +4124,9 @@ impl QueryCursor {
     }
 
     /// Set the range in which the query will be executed, in terms of rows and columns.
+    ///
+    /// The range remains in effect for later executions of this cursor. An end of
+    /// `(0, 0)` means that the range extends to the end of the document.
     #[doc(alias = "ts_query_cursor_set_point_range")]
     pub fn set_point_range(&mut self, range: ops::Range<Point>) -> &mut Self {
         unsafe {
@@ -1926,6 +4138,158 @@ impl QueryCursor {
         }
         self
     }
+
+    /// Get the cursor's current cancellation flag pointer.
+    ///
+    /// # Safety
+    ///
+    /// The flag that was assigned with [QueryCursor::set_cancellation_flag] must
+    /// still be alive.
+    #[doc(alias = "ts_query_cursor_cancellation_flag")]
+    pub unsafe fn cancellation_flag(&self) -> Option<&AtomicUsize> {
+        (ffi::ts_query_cursor_cancellation_flag(self.ptr.as_ptr()) as *const AtomicUsize).as_ref()
+    }
+
+    /// Set the cursor's current cancellation flag pointer.
+    ///
+    /// If a pointer is assigned, then the cursor will periodically read from this
+    /// pointer while searching for matches. If it reads a non-zero value, it will halt
+    /// early, and the current [QueryMatches] or [QueryCaptures] iterator will end.
+    /// Like the cursor's ranges, the flag remains in effect for later executions.
+    ///
+    /// # Safety
+    ///
+    /// The flag must remain alive for as long as it is assigned to this cursor.
+    #[doc(alias = "ts_query_cursor_set_cancellation_flag")]
+    pub unsafe fn set_cancellation_flag(&mut self, flag: Option<&AtomicUsize>) {
+        if let Some(flag) = flag {
+            ffi::ts_query_cursor_set_cancellation_flag(
+                self.ptr.as_ptr(),
+                flag as *const AtomicUsize as *const usize,
+            );
+        } else {
+            ffi::ts_query_cursor_set_cancellation_flag(self.ptr.as_ptr(), ptr::null());
+        }
+    }
+}
+
+impl QueryCursorBuilder {
+    /// Restrict the cursor to the given range of byte offsets.
+    ///
+    /// See `QueryCursor::set_byte_range`.
+    pub fn byte_range(mut self, range: ops::Range<usize>) -> Self {
+        self.byte_range = Some(range);
+        self
+    }
+
+    /// Restrict the cursor to the given range of rows and columns.
+    ///
+    /// See `QueryCursor::set_point_range`.
+    pub fn point_range(mut self, range: ops::Range<Point>) -> Self {
+        self.point_range = Some(range);
+        self
+    }
+
+    /// Set the maximum number of in-progress matches for the cursor.
+    ///
+    /// See `QueryCursor::set_match_limit`.
+    pub fn match_limit(mut self, limit: u32) -> Self {
+        self.match_limit = Some(limit);
+        self
+    }
+
+    /// Create the configured `QueryCursor`.
+    ///
+    /// If both a byte range and a point range are given, the cursor only visits nodes
+    /// that intersect both of them.
+    pub fn build(self) -> QueryCursor {
+        let mut cursor = QueryCursor::new();
+        if let Some(range) = self.byte_range {
+            cursor.set_byte_range(range);
+        }
+        if let Some(range) = self.point_range {
+            cursor.set_point_range(range);
+        }
+        if let Some(limit) = self.match_limit {
+            cursor.set_match_limit(limit);
+        }
+        cursor
+    }
+}
+
+/// Run a highlights query over a syntax tree, and return the resulting highlighted
+/// spans in order.
+///
+/// Each span is paired with the index of the capture that highlights it, which can
+/// be used to look up the capture's name in [Query::capture_names]. The spans do not
+/// overlap, and follow the standard highlighting precedence rules:
+///
+/// * When several patterns capture the same node, the pattern that appears earliest
+///   in the query wins. See [QueryCursor::captures_deduped].
+/// * When a captured node contains other captured nodes, the inner highlights take
+///   precedence, and the outer highlight is split into the spans around them.
+///
+/// Text that is not captured by any pattern is not included, and language injections
+/// are not handled.
+pub fn highlight(tree: &Tree, query: &Query, source: &[u8]) -> Vec<(Range, usize)> {
+    fn push_span(
+        spans: &mut Vec<(Range, usize)>,
+        start: (usize, Point),
+        end: (usize, Point),
+        capture_index: usize,
+    ) {
+        if start.0 < end.0 {
+            spans.push((
+                Range {
+                    start_byte: start.0,
+                    end_byte: end.0,
+                    start_point: start.1,
+                    end_point: end.1,
+                },
+                capture_index,
+            ));
+        }
+    }
+
+    // Captures that start at the same position are not necessarily ordered from the
+    // outermost to the innermost node, so sort them before nesting them.
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor
+        .captures_deduped(query, tree.root_node(), source)
+        .map(|(m, i)| (m.captures[i].node.range(), m.captures[i].index as usize))
+        .filter(|(range, _)| range.start_byte < range.end_byte)
+        .collect::<Vec<_>>();
+    captures.sort_by_key(|(range, _)| (range.start_byte, cmp::Reverse(range.end_byte)));
+
+    let mut spans = Vec::new();
+    let mut stack: Vec<(Range, usize)> = Vec::new();
+    let mut position = (0, Point::default());
+    for (range, capture_index) in captures {
+        // Close any highlights that end before this one starts.
+        while let Some((open_range, open_index)) = stack.last().cloned() {
+            if open_range.end_byte > range.start_byte {
+                break;
+            }
+            let end = (open_range.end_byte, open_range.end_point);
+            push_span(&mut spans, position, end, open_index);
+            position = end;
+            stack.pop();
+        }
+
+        // The enclosing highlight applies up to the start of this one.
+        let start = (range.start_byte, range.start_point);
+        if let Some((_, open_index)) = stack.last() {
+            push_span(&mut spans, position, start, *open_index);
+        }
+        position = start;
+        stack.push((range, capture_index));
+    }
+    while let Some((open_range, open_index)) = stack.pop() {
+        let end = (open_range.end_byte, open_range.end_point);
+        push_span(&mut spans, position, end, open_index);
+        position = end;
+    }
+    spans
 }
 
 impl<'a, 'tree> QueryMatch<'a, 'tree> {
@@ -1933,6 +4297,97 @@ impl<'a, 'tree> QueryMatch<'a, 'tree> {
         self.id
     }
 
+    /// Get the number of captures in this match.
+    pub fn capture_count(&self) -> usize {
+        self.captures.len()
+    }
+
+    /// Get the capture at the given index within this match.
+    ///
+    /// The captures are borrowed directly from the cursor's buffer, so this
+    /// does not allocate or re-validate the captured node.
+    pub fn capture(&self, i: usize) -> Option<QueryCapture<'tree>> {
+        self.captures.get(i).copied()
+    }
+
+    /// Iterate over this match's captures, pairing each captured node with the
+    /// name of its capture in the given query.
+    pub fn captures_by_name<'s, 'q: 's>(
+        &'s self,
+        query: &'q Query,
+    ) -> impl Iterator<Item = (&'q str, Node<'tree>)> + 's {
+        self.captures.iter().map(move |capture| {
+            (
+                query.capture_names[capture.index as usize].as_str(),
+                capture.node,
+            )
+        })
+    }
+
+    /// Get this match's captures, ordered by where their capture names first appear
+    /// in the source of the pattern that matched.
+    ///
+    /// The [captures](QueryMatch::captures) field lists the captures in the order that
+    /// the pattern's nodes were matched, so a capture on a node comes before the
+    /// captures on its descendants, even though it is written after them. For example,
+    /// `(call_expression function: (identifier) @function) @call` yields `@call` before
+    /// `@function`, but this method returns `@function` first. Captures with the same
+    /// name, such as the nodes of a quantified capture, keep their relative order.
+    pub fn captures_in_pattern_order(&self, query: &Query) -> Vec<QueryCapture<'tree>> {
+        let start = query.start_byte_for_pattern(self.pattern_index);
+        let end = if self.pattern_index + 1 < query.pattern_count() {
+            query.start_byte_for_pattern(self.pattern_index + 1)
+        } else {
+            query.source.len()
+        };
+        let source = &query.source[start..end];
+
+        // Find the capture names in the order that they first appear, skipping over
+        // strings and comments.
+        let mut names = Vec::new();
+        let mut i = 0;
+        while i < source.len() {
+            match source[i] {
+                b'"' => {
+                    i += 1;
+                    while i < source.len() && source[i] != b'"' {
+                        if source[i] == b'\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                }
+                b';' => {
+                    while i < source.len() && source[i] != b'\n' {
+                        i += 1;
+                    }
+                }
+                b'@' => {
+                    let name_start = i + 1;
+                    while i + 1 < source.len()
+                        && !source[i + 1].is_ascii_whitespace()
+                        && !b"()[]\";:@".contains(&source[i + 1])
+                    {
+                        i += 1;
+                    }
+                    let name = &source[name_start..i + 1];
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let mut result = self.captures.to_vec();
+        result.sort_by_key(|capture| {
+            let name = query.capture_names[capture.index as usize].as_bytes();
+            names.iter().position(|n| *n == name).unwrap_or(names.len())
+        });
+        result
+    }
+
     #[doc(alias = "ts_query_cursor_remove_match")]
     pub fn remove(self) {
         unsafe { ffi::ts_query_cursor_remove_match(self.cursor, self.id) }
@@ -1976,19 +4431,19 @@ impl<'a, 'tree> QueryMatch<'a, 'tree> {
         buffer2: &mut Vec<u8>,
         text_provider: &mut impl TextProvider<'a>,
     ) -> bool {
-        fn get_text<'a, 'b: 'a, I: Iterator<Item = &'b [u8]>>(
-            buffer: &'a mut Vec<u8>,
-            mut chunks: I,
-        ) -> &'a [u8] {
-            let first_chunk = chunks.next().unwrap_or(&[]);
+        fn get_text<'a: 'b, 'b>(
+            buffer: &'b mut Vec<u8>,
+            mut chunks: impl Iterator<Item = Cow<'a, [u8]>>,
+        ) -> Cow<'b, [u8]> {
+            let first_chunk = chunks.next().unwrap_or(Cow::Borrowed(&[]));
             if let Some(next_chunk) = chunks.next() {
                 buffer.clear();
-                buffer.extend_from_slice(first_chunk);
-                buffer.extend_from_slice(next_chunk);
+                buffer.extend_from_slice(&first_chunk);
+                buffer.extend_from_slice(&next_chunk);
                 for chunk in chunks {
-                    buffer.extend_from_slice(chunk);
+                    buffer.extend_from_slice(&chunk);
                 }
-                buffer.as_slice()
+                Cow::Borrowed(buffer.as_slice())
             } else {
                 first_chunk
             }
@@ -2014,7 +4469,29 @@ impl<'a, 'tree> QueryMatch<'a, 'tree> {
                     match node {
                         Some(node) => {
                             let text = get_text(buffer1, text_provider.text(node));
-                            (text == s.as_bytes()) == *is_positive
+                            (*text == *s.as_bytes()) == *is_positive
+                        }
+                        None => true,
+                    }
+                }
+                TextPredicate::CaptureEqCaptureCi(i, j, is_positive) => {
+                    let node1 = self.nodes_for_capture_index(*i).next();
+                    let node2 = self.nodes_for_capture_index(*j).next();
+                    match (node1, node2) {
+                        (Some(node1), Some(node2)) => {
+                            let text1 = get_text(buffer1, text_provider.text(node1));
+                            let text2 = get_text(buffer2, text_provider.text(node2));
+                            text1.eq_ignore_ascii_case(&text2) == *is_positive
+                        }
+                        _ => true,
+                    }
+                }
+                TextPredicate::CaptureEqStringCi(i, s, is_positive) => {
+                    let node = self.nodes_for_capture_index(*i).next();
+                    match node {
+                        Some(node) => {
+                            let text = get_text(buffer1, text_provider.text(node));
+                            text.eq_ignore_ascii_case(s.as_bytes()) == *is_positive
                         }
                         None => true,
                     }
@@ -2024,15 +4501,86 @@ impl<'a, 'tree> QueryMatch<'a, 'tree> {
                     match node {
                         Some(node) => {
                             let text = get_text(buffer1, text_provider.text(node));
-                            r.is_match(text) == *is_positive
+                            r.is_match(&text) == *is_positive
                         }
                         None => true,
                     }
                 }
+                TextPredicate::Custom(predicate, registered) => {
+                    (registered.evaluate)(predicate, self, &mut |node| {
+                        get_text(buffer1, text_provider.text(node)).into_owned()
+                    })
+                }
             })
     }
 }
 
+impl PredicateRegistry {
+    /// Create a new registry with no custom predicates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom predicate function with the given name, which should
+    /// not include the leading `#`.
+    ///
+    /// `validate` is called with each use of the predicate when a query is created,
+    /// and returns an error message if its arguments are invalid. `evaluate` is
+    /// called with each match of a pattern that uses the predicate, along with a
+    /// function that returns the text of a node, and returns whether the match
+    /// should be kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is the name of one of the built-in predicates: `eq?`,
+    /// `not-eq?`, `eq-ci?`, `not-eq-ci?`, `match?`, `not-match?`, `set!`, `is?` or
+    /// `is-not?`.
+    pub fn register<V, E>(&mut self, name: &str, validate: V, evaluate: E) -> &mut Self
+    where
+        V: Fn(&QueryPredicate) -> Result<(), String> + Send + Sync + 'static,
+        E: Fn(&QueryPredicate, &QueryMatch, &mut dyn FnMut(Node) -> Vec<u8>) -> bool
+            + Send
+            + Sync
+            + 'static,
+    {
+        if BUILTIN_PREDICATES.contains(&name) {
+            panic!("Cannot register built-in predicate #{}", name);
+        }
+        self.predicates.insert(
+            name.to_string(),
+            Arc::new(RegisteredPredicate {
+                validate: Box::new(validate),
+                evaluate: Box::new(evaluate),
+            }),
+        );
+        self
+    }
+
+    /// Check if a predicate with the given name can be used, either because it is
+    /// built in or because it has been registered.
+    pub fn contains(&self, name: &str) -> bool {
+        BUILTIN_PREDICATES.contains(&name) || self.predicates.contains_key(name)
+    }
+}
+
+const BUILTIN_PREDICATES: &[&str] = &[
+    "eq?",
+    "not-eq?",
+    "eq-ci?",
+    "not-eq-ci?",
+    "match?",
+    "not-match?",
+    "set!",
+    "is?",
+    "is-not?",
+];
+
+impl fmt::Debug for RegisteredPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RegisteredPredicate")
+    }
+}
+
 impl QueryProperty {
     pub fn new(key: &str, value: Option<&str>, capture_id: Option<usize>) -> Self {
         QueryProperty {
@@ -2100,6 +4648,156 @@ impl<'a, 'tree, T: TextProvider<'a>> Iterator for QueryCaptures<'a, 'tree, T> {
     }
 }
 
+/// The number of matches that `DeadlineQueryMatches` yields between checks of the clock.
+const MATCHES_PER_DEADLINE_CHECK: usize = 8;
+
+impl<'a, 'tree, T: TextProvider<'a>> Iterator for DeadlineQueryMatches<'a, 'tree, T> {
+    type Item = QueryMatch<'a, 'tree>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.deadline_exceeded {
+            return None;
+        }
+        if self.matches_until_check == 0 {
+            if Instant::now() >= self.deadline {
+                self.deadline_exceeded = true;
+                return None;
+            }
+            self.matches_until_check = MATCHES_PER_DEADLINE_CHECK;
+        }
+        self.matches_until_check -= 1;
+        self.matches.next()
+    }
+}
+
+impl<'a, 'tree, T: TextProvider<'a>> DeadlineQueryMatches<'a, 'tree, T> {
+    /// Check if the iteration stopped early because the deadline passed.
+    pub fn did_exceed_deadline(&self) -> bool {
+        self.deadline_exceeded
+    }
+}
+
+impl<'a, 'tree, T: TextProvider<'a>> Iterator for QueryCapturesWithText<'a, 'tree, T> {
+    type Item = (usize, QueryCapture<'tree>, Cow<'a, [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (m, capture_index) = self.captures.next()?;
+        let capture = m.captures[capture_index];
+        let cache = &mut self.captures.text_provider;
+
+        // Captures are ordered by start position, so the text of nodes that start
+        // earlier is unlikely to be needed again.
+        let start_byte = capture.node.start_byte();
+        cache.texts.retain(|(_, start, _)| *start >= start_byte);
+        let text = cache.get(capture.node);
+        Some((m.pattern_index, capture, text))
+    }
+}
+
+impl<'a, T: TextProvider<'a>> TextCache<'a, T> {
+    fn get(&mut self, node: Node) -> Cow<'a, [u8]> {
+        let id = node.id();
+        if let Some((_, _, text)) = self.texts.iter().find(|(cached_id, _, _)| *cached_id == id) {
+            return text.clone();
+        }
+        let text = join_chunks(
+            self.text_provider.text(node),
+            node.end_byte() - node.start_byte(),
+        );
+        self.texts.push((id, node.start_byte(), text.clone()));
+        text
+    }
+}
+
+impl<'a, T: TextProvider<'a>> TextProvider<'a> for TextCache<'a, T> {
+    type I = iter::Once<Cow<'a, [u8]>>;
+
+    fn text(&mut self, node: Node) -> Self::I {
+        iter::once(self.get(node))
+    }
+}
+
+impl<'a, 'tree, T: TextProvider<'a>> Iterator for DedupedQueryCaptures<'a, 'tree, T> {
+    type Item = (QueryMatch<'a, 'tree>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (m, capture_index) = self.captures.next()?;
+            let range = m.captures[capture_index].node.byte_range();
+
+            // Captures are ordered by start position, so only the ranges that start at the
+            // same position as this one need to be remembered.
+            if self.seen_ranges.first().map(|seen| seen.start) != Some(range.start) {
+                self.seen_ranges.clear();
+            }
+            if self.seen_ranges.contains(&range) {
+                continue;
+            }
+            self.seen_ranges.push(range);
+            return Some((m, capture_index));
+        }
+    }
+}
+
+impl<'a, 'tree, T: TextProvider<'a>> PriorityQueryCaptures<'a, 'tree, T> {
+    fn peek(&mut self) -> Option<(usize, QueryCapture<'tree>)> {
+        while self.pending.is_none() {
+            let (m, capture_index) = self.captures.next()?;
+            let capture = m.captures[capture_index];
+            if capture.node.start_byte() < capture.node.end_byte() {
+                self.pending = Some((m.pattern_index, capture));
+            }
+        }
+        self.pending
+    }
+}
+
+impl<'a, 'tree, T: TextProvider<'a>> Iterator for PriorityQueryCaptures<'a, 'tree, T> {
+    type Item = (usize, QueryCapture<'tree>, ops::Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Captures are ordered by start position, so every capture that covers the
+            // current position has already been taken from the underlying iterator.
+            while let Some((_, capture)) = self.peek() {
+                if capture.node.start_byte() > self.position {
+                    break;
+                }
+                self.active.extend(self.pending.take());
+            }
+            let position = self.position;
+            self.active
+                .retain(|(_, capture)| capture.node.end_byte() > position);
+
+            let Some(&(pattern_index, capture)) = self
+                .active
+                .iter()
+                .min_by_key(|(pattern_index, _)| *pattern_index)
+            else {
+                self.position = self.peek()?.1.node.start_byte();
+                continue;
+            };
+
+            // The winner keeps winning until it ends, or until a capture from an
+            // earlier pattern starts.
+            let mut end = capture.node.end_byte();
+            while let Some((next_pattern_index, next_capture)) = self.peek() {
+                let start = next_capture.node.start_byte();
+                if start >= end {
+                    break;
+                }
+                if next_pattern_index < pattern_index {
+                    end = start;
+                    break;
+                }
+                self.active.extend(self.pending.take());
+            }
+            self.position = end;
+            return Some((pattern_index, capture, position..end));
+        }
+    }
+}
+
 impl<'a, 'tree, T: TextProvider<'a>> QueryMatches<'a, 'tree, T> {
     #[doc(alias = "ts_query_cursor_set_byte_range")]
     pub fn set_byte_range(&mut self, range: ops::Range<usize>) {
@@ -2142,23 +4840,93 @@ impl<'cursor, 'tree> fmt::Debug for QueryMatch<'cursor, 'tree> {
     }
 }
 
-impl<'a, F, I> TextProvider<'a> for F
+impl<'cursor, 'tree> fmt::Display for QueryMatch<'cursor, 'tree> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "pattern {}, {} captures: [",
+            self.pattern_index,
+            self.captures.len()
+        )?;
+        for (i, capture) in self.captures.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", capture)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<'tree> fmt::Display for QueryCapture<'tree> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "@{} {} {} - {}",
+            self.index,
+            self.node.kind(),
+            self.node.start_position(),
+            self.node.end_position()
+        )
+    }
+}
+
+impl fmt::Debug for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Query {{ pattern_count: {}, capture_names: {:?}, predicates: [",
+            self.pattern_count(),
+            self.capture_names
+        )?;
+        for i in 0..self.pattern_count() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[")?;
+            for (j, predicate) in self.pattern_predicates(i).enumerate() {
+                if j > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", predicate)?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "] }}")
+    }
+}
+
+impl fmt::Display for PredicateInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(#{}", self.operator)?;
+        for arg in &self.args {
+            match arg {
+                PredicateInfoArg::Capture { name, .. } => write!(f, " @{}", name)?,
+                PredicateInfoArg::String(value) => write!(f, " {:?}", value)?,
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+impl<'a, F, I, C> TextProvider<'a> for F
 where
     F: FnMut(Node) -> I,
-    I: Iterator<Item = &'a [u8]> + 'a,
+    I: Iterator<Item = C> + 'a,
+    C: Into<Cow<'a, [u8]>> + 'a,
 {
-    type I = I;
+    type I = iter::Map<I, fn(C) -> Cow<'a, [u8]>>;
 
     fn text(&mut self, node: Node) -> Self::I {
-        (self)(node)
+        (self)(node).map(Into::into)
     }
 }
 
 impl<'a> TextProvider<'a> for &'a [u8] {
-    type I = iter::Once<&'a [u8]>;
+    type I = iter::Once<Cow<'a, [u8]>>;
 
     fn text(&mut self, node: Node) -> Self::I {
-        iter::once(&self[node.byte_range()])
+        iter::once(Cow::Borrowed(&self[node.byte_range()]))
     }
 }
 
@@ -2168,6 +4936,24 @@ impl PartialEq for Query {
     }
 }
 
+impl Clone for Query {
+    /// Clone this query by recompiling it from its original source.
+    ///
+    /// Any captures or patterns that have been disabled on this query are also
+    /// disabled on the clone.
+    fn clone(&self) -> Self {
+        let mut result = Query::compile(self.language, &self.source, &self.predicate_registry)
+            .expect("Query source that compiled once should compile again");
+        for name in &self.disabled_captures {
+            result.disable_capture(name);
+        }
+        for index in &self.disabled_patterns {
+            result.disable_pattern(*index);
+        }
+        result
+    }
+}
+
 impl Drop for Query {
     fn drop(&mut self) {
         unsafe { ffi::ts_query_delete(self.ptr.as_ptr()) }
@@ -2180,10 +4966,79 @@ impl Drop for QueryCursor {
     }
 }
 
+impl LookaheadIterator {
+    /// Get the current symbol of the iterator, which is the symbol that was most
+    /// recently returned by [Iterator::next].
+    #[doc(alias = "ts_lookahead_iterator_current_symbol")]
+    pub fn current_symbol(&self) -> u16 {
+        unsafe { ffi::ts_lookahead_iterator_current_symbol(self.0.as_ptr()) }
+    }
+
+    /// Get the name of the iterator's current symbol.
+    #[doc(alias = "ts_lookahead_iterator_current_symbol_name")]
+    pub fn current_symbol_name(&self) -> &'static str {
+        unsafe {
+            CStr::from_ptr(ffi::ts_lookahead_iterator_current_symbol_name(
+                self.0.as_ptr(),
+            ))
+        }
+        .to_str()
+        .unwrap()
+    }
+
+    /// Iterate over the names of the remaining symbols, rather than their ids.
+    pub fn iter_names(&mut self) -> impl Iterator<Item = &'static str> + '_ {
+        iter::from_fn(move || {
+            self.next()?;
+            Some(self.current_symbol_name())
+        })
+    }
+}
+
+impl Iterator for LookaheadIterator {
+    type Item = u16;
+
+    #[doc(alias = "ts_lookahead_iterator_next")]
+    fn next(&mut self) -> Option<Self::Item> {
+        if unsafe { ffi::ts_lookahead_iterator_next(self.0.as_ptr()) } {
+            Some(self.current_symbol())
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for LookaheadIterator {
+    fn drop(&mut self) {
+        unsafe { ffi::ts_lookahead_iterator_delete(self.0.as_ptr()) }
+    }
+}
+
 impl Point {
     pub fn new(row: usize, column: usize) -> Self {
         Point { row, column }
     }
+
+    /// Compute the extent that spans from `other` to this point, or `(0, 0)` if this
+    /// point is not after `other`.
+    ///
+    /// As in Tree-sitter's own position arithmetic, the result is relative to `other`:
+    /// if the points are on different rows, the result's column is this point's column,
+    /// and otherwise it is the difference between the columns.
+    pub fn saturating_sub(self, other: Point) -> Point {
+        if self <= other {
+            Point::default()
+        } else if self.row > other.row {
+            Point::new(self.row - other.row, self.column)
+        } else {
+            Point::new(0, self.column - other.column)
+        }
+    }
+
+    /// Get this point, or `max` if this point comes after it.
+    pub fn clamp_to(self, max: Point) -> Point {
+        self.min(max)
+    }
 }
 
 impl fmt::Display for Point {
@@ -2192,6 +5047,224 @@ impl fmt::Display for Point {
     }
 }
 
+/// Compare a `Point` with a `(row, column)` tuple.
+impl PartialEq<(usize, usize)> for Point {
+    fn eq(&self, other: &(usize, usize)) -> bool {
+        (self.row, self.column) == *other
+    }
+}
+
+impl PartialEq<Point> for (usize, usize) {
+    fn eq(&self, other: &Point) -> bool {
+        *self == (other.row, other.column)
+    }
+}
+
+/// Order a `Point` relative to a `(row, column)` tuple, in the same way that
+/// points are ordered relative to each other.
+impl PartialOrd<(usize, usize)> for Point {
+    fn partial_cmp(&self, other: &(usize, usize)) -> Option<cmp::Ordering> {
+        (self.row, self.column).partial_cmp(other)
+    }
+}
+
+impl PartialOrd<Point> for (usize, usize) {
+    fn partial_cmp(&self, other: &Point) -> Option<cmp::Ordering> {
+        self.partial_cmp(&(other.row, other.column))
+    }
+}
+
+/// Borrow the text if it consists of a single chunk, and otherwise concatenate
+/// the chunks into a buffer with the given capacity.
+fn join_chunks<'a>(
+    mut chunks: impl Iterator<Item = Cow<'a, [u8]>>,
+    capacity: usize,
+) -> Cow<'a, [u8]> {
+    let first_chunk = match chunks.next() {
+        Some(chunk) => chunk,
+        None => return Cow::Borrowed(&[]),
+    };
+    let second_chunk = match chunks.next() {
+        Some(chunk) => chunk,
+        None => return first_chunk,
+    };
+    let mut result = Vec::with_capacity(capacity);
+    result.extend_from_slice(&first_chunk);
+    result.extend_from_slice(&second_chunk);
+    for chunk in chunks {
+        result.extend_from_slice(&chunk);
+    }
+    Cow::Owned(result)
+}
+
+/// Compute the position of the given byte offset within some source code.
+///
+/// As in the positions that Tree-sitter reports for nodes, rows are separated by
+/// `\n` characters, and columns are measured in bytes. An offset past the end of
+/// `source` is treated as the end of `source`.
+pub fn byte_to_point(source: &[u8], byte: usize) -> Point {
+    let prefix = &source[..byte.min(source.len())];
+    let row = prefix.iter().filter(|b| **b == b'\n').count();
+    let line_start = prefix
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    Point::new(row, prefix.len() - line_start)
+}
+
+/// Compute the byte offset of the given position within some source code.
+///
+/// This is the inverse of [byte_to_point]. Returns `None` if the position's row
+/// is past the last line of `source`, or if its column is past the end of that
+/// row.
+pub fn point_to_byte(source: &[u8], point: Point) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..point.row {
+        line_start += source[line_start..].iter().position(|b| *b == b'\n')? + 1;
+    }
+    let line_len = source[line_start..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .unwrap_or(source.len() - line_start);
+    if point.column > line_len {
+        return None;
+    }
+    Some(line_start + point.column)
+}
+
+impl InputEdit {
+    /// Compute the edits that describe replacing several ranges of a document.
+    ///
+    /// Each change consists of a byte range in the original `text` and the text
+    /// that replaces it. The changes may be given in any order, but they must not
+    /// overlap. Returns the edits, in a form suitable for [Tree::edit_all], along
+    /// with the new text of the document.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two changes overlap, or if a range is reversed or out of bounds
+    /// for `text`.
+    pub fn from_changes<T: AsRef<[u8]>>(
+        text: &[u8],
+        changes: &[(ops::Range<usize>, T)],
+    ) -> (Vec<InputEdit>, Vec<u8>) {
+        fn advance(mut point: Point, bytes: &[u8]) -> Point {
+            match bytes.iter().rposition(|b| *b == b'\n') {
+                Some(i) => {
+                    point.row += bytes.iter().filter(|b| **b == b'\n').count();
+                    point.column = bytes.len() - i - 1;
+                }
+                None => point.column += bytes.len(),
+            }
+            point
+        }
+
+        let mut changes = changes.iter().collect::<Vec<_>>();
+        changes.sort_by_key(|(range, _)| range.start);
+
+        let mut edits = Vec::with_capacity(changes.len());
+        let mut new_text = Vec::with_capacity(text.len());
+        let mut position = Point::default();
+        let mut copied = 0;
+        for (range, replacement) in changes {
+            let replacement = replacement.as_ref();
+            assert!(
+                copied <= range.start && range.start <= range.end && range.end <= text.len(),
+                "Invalid or overlapping change range {:?}",
+                range,
+            );
+            position = advance(position, &text[copied..range.start]);
+            new_text.extend_from_slice(&text[copied..range.start]);
+
+            let start_byte = new_text.len();
+            let start_position = position;
+            position = advance(start_position, replacement);
+            new_text.extend_from_slice(replacement);
+            edits.push(InputEdit {
+                start_byte,
+                old_end_byte: start_byte + range.len(),
+                new_end_byte: new_text.len(),
+                start_position,
+                old_end_position: advance(start_position, &text[range.clone()]),
+                new_end_position: position,
+            });
+            copied = range.end;
+        }
+        new_text.extend_from_slice(&text[copied..]);
+        (edits, new_text)
+    }
+}
+
+impl Range {
+    pub fn new(start_byte: usize, end_byte: usize, start_point: Point, end_point: Point) -> Self {
+        Range {
+            start_byte,
+            end_byte,
+            start_point,
+            end_point,
+        }
+    }
+
+    /// Create a zero-width range at the given position, such as a cursor position
+    /// in an editor.
+    pub fn empty_at(byte: usize, point: Point) -> Self {
+        Range::new(byte, byte, point, point)
+    }
+
+    /// Get the range spanned by the given node.
+    ///
+    /// This is equivalent to [Node::range].
+    pub fn from_node(node: &Node) -> Self {
+        node.range()
+    }
+
+    /// Get the span of rows and columns covered by this range.
+    pub fn point_range(&self) -> ops::Range<Point> {
+        self.start_point..self.end_point
+    }
+
+    /// Combine the given ranges into a minimal set of ranges, sorted by their
+    /// start byte, in which no two ranges overlap or touch.
+    ///
+    /// This is useful for post-processing the output of [Tree::changed_ranges], or
+    /// any other collection of ranges that need to be revisited.
+    pub fn merge_all(ranges: impl IntoIterator<Item = Range>) -> Vec<Range> {
+        let mut ranges = ranges.into_iter().collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|range| (range.start_byte, range.end_byte));
+
+        let mut result: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if let Some(last) = result.last_mut() {
+                if range.start_byte <= last.end_byte {
+                    if range.end_byte > last.end_byte {
+                        last.end_byte = range.end_byte;
+                        last.end_point = range.end_point;
+                    }
+                    continue;
+                }
+            }
+            result.push(range);
+        }
+        result
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "{}..{} {}-{}",
+            self.start_byte, self.end_byte, self.start_point, self.end_point
+        )
+    }
+}
+
+impl From<Range> for ops::Range<usize> {
+    fn from(range: Range) -> Self {
+        range.start_byte..range.end_byte
+    }
+}
+
 impl Into<ffi::TSPoint> for Point {
     fn into(self) -> ffi::TSPoint {
         ffi::TSPoint {
@@ -2291,6 +5364,45 @@ impl<'a> Iterator for LossyUtf8<'a> {
     }
 }
 
+/// Find the byte offset of the regex literal in the `nth` `#match?` or `#not-match?`
+/// predicate of the pattern that starts at `pattern_start`, skipping over string
+/// literals and comments.
+fn regex_offset(source: &[u8], pattern_start: usize, nth: usize) -> Option<usize> {
+    let mut remaining = nth;
+    let mut i = pattern_start;
+    while i < source.len() {
+        match source[i] {
+            b'"' => {
+                i += 1;
+                while i < source.len() && source[i] != b'"' {
+                    if source[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b';' => {
+                while i < source.len() && source[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'#' => {
+                let name = &source[i + 1..];
+                if name.starts_with(b"match?") || name.starts_with(b"not-match?") {
+                    if remaining == 0 {
+                        // The regex is the predicate's only string argument.
+                        return source[i..].iter().position(|b| *b == b'"').map(|j| i + j);
+                    }
+                    remaining -= 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
 fn predicate_error(row: usize, message: String) -> QueryError {
     QueryError {
         kind: QueryErrorKind::Predicate,
@@ -2301,6 +5413,27 @@ fn predicate_error(row: usize, message: String) -> QueryError {
     }
 }
 
+impl QueryError {
+    /// Get the position of this error's [offset](QueryError::offset) within the given
+    /// query source.
+    ///
+    /// Returns `None` if the offset lies outside of `source`, or if the error has no
+    /// position: [QueryErrorKind::Language] errors never have one, and
+    /// [QueryErrorKind::Predicate] errors only have one if their offset is nonzero,
+    /// which is the case for invalid regexes.
+    pub fn location(&self, source: &str) -> Option<Point> {
+        let has_position = match self.kind {
+            QueryErrorKind::Language => false,
+            QueryErrorKind::Predicate => self.offset != 0,
+            _ => true,
+        };
+        if !has_position || self.offset > source.len() {
+            return None;
+        }
+        Some(byte_to_point(source.as_bytes(), self.offset))
+    }
+}
+
 impl fmt::Display for IncludedRangesError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Incorrect range by index: {}", self.0)
@@ -2343,6 +5476,41 @@ impl fmt::Display for QueryError {
     }
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Language(error) => error.fmt(f),
+            Error::IncludedRanges(error) => error.fmt(f),
+            Error::Query(error) => error.fmt(f),
+            Error::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl From<LanguageError> for Error {
+    fn from(error: LanguageError) -> Self {
+        Error::Language(error)
+    }
+}
+
+impl From<IncludedRangesError> for Error {
+    fn from(error: IncludedRangesError) -> Self {
+        Error::IncludedRanges(error)
+    }
+}
+
+impl From<QueryError> for Error {
+    fn from(error: QueryError) -> Self {
+        Error::Query(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
 extern "C" {
     fn free(ptr: *mut c_void);
 }
@@ -2364,12 +5532,25 @@ impl error::Error for IncludedRangesError {}
 impl error::Error for LanguageError {}
 impl error::Error for QueryError {}
 
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Language(error) => Some(error),
+            Error::IncludedRanges(error) => Some(error),
+            Error::Query(error) => Some(error),
+            Error::Io(error) => Some(error),
+        }
+    }
+}
+
 unsafe impl Send for Language {}
+unsafe impl Send for LookaheadIterator {}
 unsafe impl Send for Parser {}
 unsafe impl Send for Query {}
 unsafe impl Send for QueryCursor {}
 unsafe impl Send for Tree {}
 unsafe impl Sync for Language {}
+unsafe impl Sync for LookaheadIterator {}
 unsafe impl Sync for Parser {}
 unsafe impl Sync for Query {}
 unsafe impl Sync for QueryCursor {}
@@ -33,6 +33,7 @@ extern "C" {
 /* Section - Types */
 /*******************/
 
+typedef uint16_t TSStateId;
 typedef uint16_t TSSymbol;
 typedef uint16_t TSFieldId;
 typedef struct TSLanguage TSLanguage;
@@ -40,6 +41,7 @@ typedef struct TSParser TSParser;
 typedef struct TSTree TSTree;
 typedef struct TSQuery TSQuery;
 typedef struct TSQueryCursor TSQueryCursor;
+typedef struct TSLookaheadIterator TSLookaheadIterator;
 
 typedef enum {
   TSInputEncodingUTF8,
@@ -70,6 +72,16 @@ typedef struct {
   TSInputEncoding encoding;
 } TSInput;
 
+typedef struct {
+  void *payload;
+  uint32_t current_byte_offset;
+} TSParseState;
+
+typedef struct {
+  void *payload;
+  bool (*progress_callback)(TSParseState *state);
+} TSParseOptions;
+
 typedef enum {
   TSLogTypeParse,
   TSLogTypeLex,
@@ -256,6 +268,22 @@ TSTree *ts_parser_parse(
   TSInput input
 );
 
+/**
+ * Use the parser to parse some source code, with some additional options.
+ *
+ * This works the same as `ts_parser_parse`, but the parser also periodically
+ * invokes the `progress_callback` in the given options, passing it the parser's
+ * current state along with the options' `payload`. If the callback returns
+ * `true`, the parser will halt early, returning NULL, just as if a cancellation
+ * flag had been flipped.
+ */
+TSTree *ts_parser_parse_with_options(
+  TSParser *self,
+  const TSTree *old_tree,
+  TSInput input,
+  TSParseOptions parse_options
+);
+
 /**
  * Use the parser to parse some source code stored in one contiguous buffer.
  * The first two parameters are the same as in the `ts_parser_parse` function
@@ -294,6 +322,18 @@ TSTree *ts_parser_parse_string_encoding(
  */
 void ts_parser_reset(TSParser *self);
 
+/**
+ * Get the byte offset that the parser has reached in the document that it is
+ * currently parsing.
+ *
+ * When a parse halts early because of a timeout, a cancellation flag, or a
+ * progress callback, no partial tree is returned. This function reports how
+ * far the parser got before halting. It returns zero when no parse is in
+ * progress, including after a parse has completed successfully and after a
+ * call to `ts_parser_reset`.
+ */
+uint32_t ts_parser_current_byte_offset(const TSParser *self);
+
 /**
  * Set the maximum duration in microseconds that parsing should be allowed to
  * take before halting.
@@ -388,6 +428,16 @@ const TSLanguage *ts_tree_language(const TSTree *);
  */
 TSRange *ts_tree_included_ranges(const TSTree *, uint32_t *length);
 
+/**
+ * Get an estimate of the number of bytes of memory used by the syntax tree.
+ *
+ * Subtrees that are shared with other syntax trees, such as copies of this
+ * tree or trees that reused its nodes during an incremental parse, are counted
+ * in full, so the sizes of several related trees may add up to more than the
+ * memory that they use together.
+ */
+size_t ts_tree_size_bytes(const TSTree *self);
+
 /**
  * Edit the syntax tree to keep it in sync with source code that has been
  * edited.
@@ -436,6 +486,25 @@ const char *ts_node_type(TSNode);
  */
 TSSymbol ts_node_symbol(TSNode);
 
+/**
+ * Get the parse state in which the parser encountered this node.
+ *
+ * Nodes that have been edited, and some nodes whose parse state depends on
+ * their surroundings, do not have a parse state. For these nodes, this
+ * returns `UINT16_MAX`.
+ */
+TSStateId ts_node_parse_state(TSNode);
+
+/**
+ * Get the parse state that the parser moved to after this node.
+ *
+ * Combined with a lookahead iterator, this can be used to find the tokens
+ * that could have followed this node, for example in order to describe the
+ * tokens that were expected in place of a syntax error. Returns `UINT16_MAX`
+ * if the node does not have a parse state.
+ */
+TSStateId ts_node_next_parse_state(TSNode);
+
 /**
  * Get the node's start byte.
  */
@@ -871,6 +940,21 @@ void ts_query_cursor_set_match_limit(TSQueryCursor *, uint32_t);
 void ts_query_cursor_set_byte_range(TSQueryCursor *, uint32_t, uint32_t);
 void ts_query_cursor_set_point_range(TSQueryCursor *, TSPoint, TSPoint);
 
+/**
+ * Set the query cursor's current cancellation flag pointer.
+ *
+ * If a non-null pointer is assigned, then the cursor will periodically read
+ * from this pointer while searching for matches. If it reads a non-zero value,
+ * it will halt early, and will not find any further matches until it is
+ * executed again.
+ */
+void ts_query_cursor_set_cancellation_flag(TSQueryCursor *self, const size_t *flag);
+
+/**
+ * Get the query cursor's current cancellation flag pointer.
+ */
+const size_t *ts_query_cursor_cancellation_flag(const TSQueryCursor *self);
+
 /**
  * Advance to the next match of the currently running query.
  *
@@ -948,6 +1032,51 @@ TSSymbolType ts_language_symbol_type(const TSLanguage *, TSSymbol);
  */
 uint32_t ts_language_version(const TSLanguage *);
 
+/**
+ * Get the number of valid parse states in this language.
+ */
+uint32_t ts_language_state_count(const TSLanguage *);
+
+/********************************/
+/* Section - Lookahead Iterator */
+/********************************/
+
+/**
+ * Create a new lookahead iterator for the given language and parse state.
+ *
+ * This returns `NULL` if the state is invalid for the language.
+ *
+ * Repeatedly call `ts_lookahead_iterator_next` to visit each of the symbols
+ * that are valid in the given parse state, and call
+ * `ts_lookahead_iterator_current_symbol` to get the current symbol. The
+ * symbols include both tokens and the non-terminal symbols that the parser
+ * can reduce to in that state.
+ */
+TSLookaheadIterator *ts_lookahead_iterator_new(const TSLanguage *self, TSStateId state);
+
+/**
+ * Delete a lookahead iterator, freeing all of the memory that it used.
+ */
+void ts_lookahead_iterator_delete(TSLookaheadIterator *self);
+
+/**
+ * Advance the lookahead iterator to the next symbol.
+ *
+ * This returns `true` if there is a new symbol and `false` otherwise.
+ */
+bool ts_lookahead_iterator_next(TSLookaheadIterator *self);
+
+/**
+ * Get the current symbol of the lookahead iterator.
+ */
+TSSymbol ts_lookahead_iterator_current_symbol(const TSLookaheadIterator *self);
+
+/**
+ * Get the current symbol type of the lookahead iterator as a null terminated
+ * string.
+ */
+const char *ts_lookahead_iterator_current_symbol_name(const TSLookaheadIterator *self);
+
 /**********************************/
 /* Section - Global Configuration */
 /**********************************/
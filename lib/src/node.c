@@ -417,6 +417,17 @@ TSSymbol ts_node_symbol(TSNode self) {
   return ts_language_public_symbol(self.tree->language, symbol);
 }
 
+TSStateId ts_node_parse_state(TSNode self) {
+  return ts_subtree_parse_state(ts_node__subtree(self));
+}
+
+TSStateId ts_node_next_parse_state(TSNode self) {
+  Subtree subtree = ts_node__subtree(self);
+  TSStateId state = ts_subtree_parse_state(subtree);
+  if (state == TS_TREE_STATE_NONE) return TS_TREE_STATE_NONE;
+  return ts_language_next_state(self.tree->language, state, ts_subtree_symbol(subtree));
+}
+
 const char *ts_node_type(TSNode self) {
   TSSymbol symbol = ts_node__alias(&self);
   if (!symbol) symbol = ts_subtree_symbol(ts_node__subtree(self));
@@ -102,6 +102,8 @@ struct TSParser {
   unsigned accept_count;
   unsigned operation_count;
   const volatile size_t *cancellation_flag;
+  TSParseOptions parse_options;
+  TSParseState parse_state;
   Subtree old_tree;
   TSRangeArray included_range_differences;
   unsigned included_range_difference_index;
@@ -1453,15 +1455,17 @@ static bool ts_parser__advance(
       }
     }
 
-    // If a cancellation flag or a timeout was provided, then check every
-    // time a fixed number of parse actions has been processed.
+    // If a cancellation flag, a timeout, or a progress callback was provided,
+    // then check every time a fixed number of parse actions has been processed.
     if (++self->operation_count == OP_COUNT_PER_TIMEOUT_CHECK) {
       self->operation_count = 0;
     }
+    self->parse_state.current_byte_offset = position;
     if (
       self->operation_count == 0 &&
       ((self->cancellation_flag && atomic_load(self->cancellation_flag)) ||
-       (!clock_is_null(self->end_clock) && clock_is_gt(clock_now(), self->end_clock)))
+       (!clock_is_null(self->end_clock) && clock_is_gt(clock_now(), self->end_clock)) ||
+       (self->parse_options.progress_callback && self->parse_options.progress_callback(&self->parse_state)))
     ) {
       if (lookahead.ptr) {
         ts_subtree_release(&self->tree_pool, lookahead);
@@ -1741,6 +1745,8 @@ TSParser *ts_parser_new(void) {
   self->reusable_node = reusable_node_new();
   self->dot_graph_file = NULL;
   self->cancellation_flag = NULL;
+  self->parse_options = (TSParseOptions) {0};
+  self->parse_state = (TSParseState) {0};
   self->timeout_duration = 0;
   self->end_clock = clock_null();
   self->operation_count = 0;
@@ -1833,6 +1839,10 @@ uint64_t ts_parser_timeout_micros(const TSParser *self) {
   return duration_to_micros(self->timeout_duration);
 }
 
+uint32_t ts_parser_current_byte_offset(const TSParser *self) {
+  return self->parse_state.current_byte_offset;
+}
+
 void ts_parser_set_timeout_micros(TSParser *self, uint64_t timeout_micros) {
   self->timeout_duration = duration_from_micros(timeout_micros);
 }
@@ -1854,6 +1864,8 @@ void ts_parser_reset(TSParser *self) {
     self->language->external_scanner.deserialize(self->external_scanner_payload, NULL, 0);
   }
 
+  self->parse_state.current_byte_offset = 0;
+
   if (self->old_tree.ptr) {
     ts_subtree_release(&self->tree_pool, self->old_tree);
     self->old_tree = NULL_SUBTREE;
@@ -1980,6 +1992,20 @@ TSTree *ts_parser_parse(
   return result;
 }
 
+TSTree *ts_parser_parse_with_options(
+  TSParser *self,
+  const TSTree *old_tree,
+  TSInput input,
+  TSParseOptions parse_options
+) {
+  self->parse_options = parse_options;
+  self->parse_state.payload = parse_options.payload;
+  TSTree *result = ts_parser_parse(self, old_tree, input);
+  self->parse_options = (TSParseOptions) {0};
+  self->parse_state.payload = NULL;
+  return result;
+}
+
 TSTree *ts_parser_parse_string(
   TSParser *self,
   const TSTree *old_tree,
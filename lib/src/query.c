@@ -1,6 +1,7 @@
 #include "tree_sitter/api.h"
 #include "./alloc.h"
 #include "./array.h"
+#include "./atomic.h"
 #include "./language.h"
 #include "./point.h"
 #include "./tree_cursor.h"
@@ -310,6 +311,8 @@ struct TSQueryCursor {
   TSPoint start_point;
   TSPoint end_point;
   uint32_t next_state_id;
+  const volatile size_t *cancellation_flag;
+  unsigned operation_count;
   bool on_visible_node;
   bool ascending;
   bool halted;
@@ -320,6 +323,7 @@ static const TSQueryError PARENT_DONE = -1;
 static const uint16_t PATTERN_DONE_MARKER = UINT16_MAX;
 static const uint16_t NONE = UINT16_MAX;
 static const TSSymbol WILDCARD_SYMBOL = 0;
+static const unsigned OP_COUNT_PER_CANCELLATION_CHECK = 100;
 
 /**********
  * Stream
@@ -1258,7 +1262,7 @@ static void ts_query__perform_analysis(
       // Follow every possible path in the parse table, but only visit states that
       // are part of the subgraph for the current symbol.
       LookaheadIterator lookahead_iterator = ts_language_lookaheads(self->language, parse_state);
-      while (ts_lookahead_iterator_next(&lookahead_iterator)) {
+      while (ts_lookahead_iterator__next(&lookahead_iterator)) {
         TSSymbol sym = lookahead_iterator.symbol;
 
         AnalysisSubgraphNode successor = {
@@ -1529,7 +1533,7 @@ static bool ts_query__analyze_patterns(TSQuery *self, unsigned *error_offset) {
   for (TSStateId state = 1; state < self->language->state_count; state++) {
     unsigned subgraph_index, exists;
     LookaheadIterator lookahead_iterator = ts_language_lookaheads(self->language, state);
-    while (ts_lookahead_iterator_next(&lookahead_iterator)) {
+    while (ts_lookahead_iterator__next(&lookahead_iterator)) {
       if (lookahead_iterator.action_count) {
         for (unsigned i = 0; i < lookahead_iterator.action_count; i++) {
           const TSParseAction *action = &lookahead_iterator.actions[i];
@@ -2973,6 +2977,8 @@ TSQueryCursor *ts_query_cursor_new(void) {
     .end_byte = UINT32_MAX,
     .start_point = {0, 0},
     .end_point = POINT_MAX,
+    .cancellation_flag = NULL,
+    .operation_count = 0,
   };
   array_reserve(&self->states, 8);
   array_reserve(&self->finished_states, 8);
@@ -3015,6 +3021,7 @@ void ts_query_cursor_exec(
   self->halted = false;
   self->query = query;
   self->did_exceed_match_limit = false;
+  self->operation_count = 0;
 }
 
 void ts_query_cursor_set_byte_range(
@@ -3041,6 +3048,14 @@ void ts_query_cursor_set_point_range(
   self->end_point = end_point;
 }
 
+const size_t *ts_query_cursor_cancellation_flag(const TSQueryCursor *self) {
+  return (const size_t *)self->cancellation_flag;
+}
+
+void ts_query_cursor_set_cancellation_flag(TSQueryCursor *self, const size_t *flag) {
+  self->cancellation_flag = (const volatile size_t *)flag;
+}
+
 // Search through all of the in-progress states, and find the captured
 // node that occurs earliest in the document.
 static bool ts_query_cursor__first_in_progress_capture(
@@ -3409,6 +3424,18 @@ static inline bool ts_query_cursor__advance(
 
     if (did_match || self->halted) return did_match;
 
+    // If a cancellation flag was provided, then check it every time a fixed
+    // number of steps has been processed, and halt if it has been set.
+    if (self->cancellation_flag) {
+      if (++self->operation_count == OP_COUNT_PER_CANCELLATION_CHECK) {
+        self->operation_count = 0;
+        if (atomic_load(self->cancellation_flag)) {
+          self->halted = true;
+          continue;
+        }
+      }
+    }
+
     // Exit the current node.
     if (self->ascending) {
       if (self->on_visible_node) {
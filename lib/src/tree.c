@@ -98,6 +98,35 @@ TSRange *ts_tree_included_ranges(const TSTree *self, uint32_t *length) {
   return ranges;
 }
 
+size_t ts_tree_size_bytes(const TSTree *self) {
+  size_t result = sizeof(TSTree) + self->included_range_count * sizeof(TSRange);
+
+  // Inline subtrees are stored within their parent's array of children, so only
+  // the heap-allocated subtrees contribute to the total.
+  SubtreeArray stack = array_new();
+  array_push(&stack, self->root);
+  while (stack.size > 0) {
+    Subtree subtree = array_pop(&stack);
+    if (subtree.data.is_inline) continue;
+
+    uint32_t child_count = subtree.ptr->child_count;
+    result += sizeof(SubtreeHeapData) + child_count * sizeof(Subtree);
+    if (child_count == 0) {
+      if (subtree.ptr->has_external_tokens) {
+        const ExternalScannerState *state = &subtree.ptr->external_scanner_state;
+        if (state->length > sizeof(state->short_data)) result += state->length;
+      }
+    } else {
+      Subtree *children = ts_subtree_children(subtree);
+      for (uint32_t i = 0; i < child_count; i++) {
+        array_push(&stack, children[i]);
+      }
+    }
+  }
+  array_delete(&stack);
+  return result;
+}
+
 TSRange *ts_tree_get_changed_ranges(const TSTree *self, const TSTree *other, uint32_t *count) {
   TreeCursor cursor1 = {NULL, array_new()};
   TreeCursor cursor2 = {NULL, array_new()};